@@ -31,6 +31,18 @@ fn spawn(b: &mut Bencher) {
     });
 }
 
+fn spawn_batch(b: &mut Bencher) {
+    let mut world = World::<Bundle>::new();
+    b.iter(|| {
+        world
+            .spawn_batch((0..1_000).map(|_| Bundle {
+                pos: Position(0.0),
+                vel: Velocity(0.0),
+            }))
+            .for_each(drop);
+    });
+}
+
 fn iterate_100k(b: &mut Bencher) {
     let mut world = World::<Bundle>::new();
 
@@ -126,6 +138,7 @@ fn iterate_100k_random_access(b: &mut Bencher) {
 benchmark_group!(
     benches,
     spawn,
+    spawn_batch,
     iterate_100k,
     iterate_100k_no_id,
     iterate_100k_random_access
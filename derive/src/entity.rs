@@ -6,10 +6,7 @@ use syn::{DeriveInput, Error, Result};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
     match input.data {
-        syn::Data::Struct(syn::DataStruct {
-            fields: syn::Fields::Named(ref fields),
-            ..
-        }) => r#struct::derive(&input, fields),
+        syn::Data::Struct(ref data) => r#struct::derive(&input, &data.fields),
         syn::Data::Enum(ref data) => r#enum::derive(&input, data),
         _ => Err(Error::new_spanned(
             input.ident,
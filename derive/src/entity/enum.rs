@@ -2,9 +2,10 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{DataEnum, DeriveInput, Error, Result};
 
-use crate::utils::{associated_ident, get_attr_derives, Derives};
-
-// FIXME: Use `__stecs__` prefix for generic parameters consistently.
+use crate::utils::{
+    associated_ident, generics_with_new_lifetime, generics_with_new_type_param, get_entity_attrs,
+    EntityAttrs,
+};
 
 pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     let ident = &input.ident;
@@ -19,13 +20,73 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     let ident_ref_fetch = associated_ident(ident, "RefFetch");
     let ident_ref_mut_fetch = associated_ident(ident, "RefMutFetch");
     let ident_world_fetch = associated_ident(ident, "WorldFetch");
+    let ident_diff = associated_ident(ident, "Diff");
 
-    let Derives {
+    let EntityAttrs {
         id_derives,
         world_data_derives,
+        columns_derives,
         ref_derives,
-        ..
-    } = get_attr_derives(&input.attrs)?;
+        ref_mut_derives,
+        diff_derives,
+        kind_derives,
+        kind,
+        ordered,
+    } = get_entity_attrs(&input.attrs)?;
+
+    // Unlike the other associated types, the `Kind` enum is meant to be named
+    // by users, so it gets a readable name (e.g. `EntityKind`).
+    let ident_kind = kind.unwrap_or_else(|| quote::format_ident!("{}Kind", ident));
+
+    // These are always derived for the `Kind` enum, so deriving them again
+    // through `#[stecs(derive_kind(...))]` would conflict.
+    let kind_derives = kind_derives.without(&[
+        "Clone",
+        "Copy",
+        "Debug",
+        "PartialEq",
+        "Eq",
+        "PartialOrd",
+        "Ord",
+        "Hash",
+    ]);
+
+    if ordered {
+        return Err(Error::new_spanned(
+            ident,
+            "#[stecs(ordered)] must be specified on the variant structs, not on the enum",
+        ));
+    }
+
+    if !columns_derives.is_empty() {
+        return Err(Error::new_spanned(
+            ident,
+            "#[stecs(derive_columns(...))] must be specified on the variant structs, not on the \
+             enum",
+        ));
+    }
+
+    // Our generic parameters are passed on to all of the associated types.
+    // Some of them need an additional lifetime, and the `WorldFetch` needs an
+    // additional type parameter for the query's `Fetch`.
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let where_predicates: Vec<_> = where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .collect();
+
+    let lifetime: syn::Lifetime = syn::parse_str("'__stecs__f").unwrap();
+    let lifetime2: syn::Lifetime = syn::parse_str("'__stecs__g").unwrap();
+    let type_param: syn::TypeParam = syn::parse_str("__stecs__F").unwrap();
+
+    let generics_lifetime = generics_with_new_lifetime(&input.generics, &lifetime);
+    let (impl_generics_lifetime, ty_generics_lifetime, _) = generics_lifetime.split_for_impl();
+
+    let generics_lifetime2 = generics_with_new_lifetime(&input.generics, &lifetime2);
+    let (_, ty_generics_lifetime2, _) = generics_lifetime2.split_for_impl();
+
+    let generics_fetch = generics_with_new_type_param(&generics_lifetime, &type_param);
+    let (impl_generics_fetch, ty_generics_fetch, _) = generics_fetch.split_for_impl();
 
     // As an example, our input looks like this:
     // ```
@@ -43,19 +104,100 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         .map(|variant| variant.ident.clone())
         .collect();
 
-    let variant_tys: Vec<_> = data
+    // Variants without a payload (e.g. `Checkpoint`) are stored as a unit
+    // struct entity that we generate for them. The structs are named after
+    // their variant, so that they show up as such in `Debug`, `describe` and
+    // `Id`s, and live in a hidden module, so that they cannot clash with the
+    // user's items.
+    let ident_units = associated_ident(ident, "Units");
+    let variant_units: Vec<Option<syn::Ident>> = data
         .variants
         .iter()
         .map(|variant| match &variant.fields {
-            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
-                Ok(fields.unnamed.iter().collect::<Vec<_>>()[0].ty.clone())
-            }
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(None),
+            syn::Fields::Unit => Ok(Some(variant.ident.clone())),
             _ => Err(Error::new_spanned(
-                ident,
-                "For derive(Entity) on enums, each variant must have exactly one unnamed field",
+                &variant.ident,
+                "For derive(Entity) on enums, each variant must have either exactly one unnamed \
+                 field or no fields at all",
             )),
         })
         .collect::<Result<_>>()?;
+    let variant_unit_paths: Vec<Option<syn::Path>> = variant_units
+        .iter()
+        .map(|unit| {
+            unit.as_ref()
+                .map(|unit| syn::parse_quote! { #ident_units::#unit })
+        })
+        .collect();
+
+    let variant_tys: Vec<syn::Type> = data
+        .variants
+        .iter()
+        .zip(&variant_unit_paths)
+        .map(|(variant, unit)| match unit {
+            Some(unit) => syn::parse_quote! { #unit },
+            None => variant.fields.iter().next().unwrap().ty.clone(),
+        })
+        .collect();
+
+    let unit_struct_items = variant_units.iter().flatten().map(|unit| {
+        quote! {
+            #[derive(
+                ::stecs::Entity,
+                ::std::clone::Clone,
+                ::std::marker::Copy,
+                ::std::fmt::Debug,
+                ::std::default::Default,
+                ::std::cmp::PartialEq,
+                ::std::cmp::Eq,
+                ::std::hash::Hash,
+            )]
+            pub struct #unit;
+        }
+    });
+    let unit_structs = variant_units.iter().any(Option::is_some).then(|| {
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            mod #ident_units {
+                #(#unit_struct_items)*
+            }
+        }
+    });
+
+    // These are used wherever we convert between our enum and its variants.
+    // `variant_pats` matches a variant of the enum, binding its payload to
+    // `entity`, and `variant_inners` is an expression for the payload.
+    // `variant_outers` is an expression that wraps `entity` into the enum.
+    let variant_pats: Vec<_> = variant_idents
+        .iter()
+        .zip(&variant_unit_paths)
+        .map(|(variant_ident, unit)| match unit {
+            Some(_) => quote! { #ident::#variant_ident },
+            None => quote! { #ident::#variant_ident(entity) },
+        })
+        .collect();
+    let variant_inners: Vec<_> = variant_unit_paths
+        .iter()
+        .map(|unit| match unit {
+            Some(unit) => quote! { #unit },
+            None => quote! { entity },
+        })
+        .collect();
+    let variant_outers: Vec<_> = variant_idents
+        .iter()
+        .zip(&variant_unit_paths)
+        .map(|(variant_ident, unit)| match unit {
+            Some(unit) => quote! {
+                {
+                    let #unit = entity;
+                    #ident::#variant_ident
+                }
+            },
+            None => quote! { #ident::#variant_ident(entity) },
+        })
+        .collect();
 
     // This is the iterator type that is used to execute queries over the
     // `WorldData` associated withour `Entity` enum. It is obtained by chaining
@@ -63,40 +205,122 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     // be either structs (leafs) or enums (nodes).
     let world_fetch_iter = variant_tys
         .iter()
-        .map(|ty| quote! { ::stecs::world::EntityWorldFetchIter<'w, #ty, F> })
+        .map(|ty| quote! { ::stecs::world::EntityWorldFetchIter<#lifetime, #ty, #type_param> })
         .fold(
-            quote! { ::std::iter::Empty<F> },
+            quote! { ::std::iter::Empty<#type_param> },
             |chain, ty| quote! { ::std::iter::Chain<#chain, #ty> },
         );
 
-    let lifetime: syn::Lifetime = syn::parse_str("'__stecs__f").unwrap();
-    let type_param: syn::TypeParam = syn::parse_str("__stecs__F").unwrap();
+    // This is the iterator type that moves all entities out of our
+    // `WorldData`. Again, it chains the iterators of each of our variant types.
+    let world_data_into_iter = variant_tys
+        .iter()
+        .map(|ty| quote! { ::stecs::world::EntityWorldIntoIter<#ty, #ident #ty_generics> })
+        .fold(
+            quote! { ::std::iter::Empty<(::stecs::Id<#ident #ty_generics>, #ident #ty_generics)> },
+            |chain, ty| quote! { ::std::iter::Chain<#chain, #ty> },
+        );
 
-    // TODO: We could probably remove this requirement.
-    if !input.generics.params.is_empty() {
-        return Err(Error::new_spanned(
-            ident,
-            "derive(Entity) for enums must not have any generics",
-        ));
-    }
+    let num_variants = variant_idents.len();
+
+    // For `IdBits`, the variant is encoded with as few bits as possible.
+    let discriminants: Vec<u64> = (0..variant_idents.len() as u64).collect();
+    let discriminant_bits = match variant_idents.len() {
+        0 | 1 => 0,
+        n => usize::BITS - (n - 1).leading_zeros(),
+    };
+
+    // The `Diff` enum is generic over the variants' `Diff` types, so that it
+    // can be defined even if some variants do not implement `EntityDiff`.
+    let diff_type_params: Vec<_> = variant_idents
+        .iter()
+        .map(|variant_ident| associated_ident(variant_ident, "Diff"))
+        .collect();
+
+    // Unless `Debug` is derived explicitly, we implement it for `Ref` and
+    // `RefMut` whenever all variants implement it. Variants without a payload
+    // are printed by name only.
+    let variant_debugs: Vec<_> = variant_idents
+        .iter()
+        .zip(&variant_units)
+        .map(|(variant_ident, unit)| match unit {
+            Some(_) => quote! {
+                {
+                    let _ = entity;
+                    f.write_str(::std::stringify!(#variant_ident))
+                }
+            },
+            None => quote! {
+                f.debug_tuple(::std::stringify!(#variant_ident)).field(entity).finish()
+            },
+        })
+        .collect();
+
+    let ref_debug = (!ref_derives.contains("Debug")).then(|| {
+        quote! {
+            impl #impl_generics_lifetime ::std::fmt::Debug for #ident_ref #ty_generics_lifetime
+            where
+                #(#where_predicates,)*
+                // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+                #(
+                    for<'__stecs__a> <#variant_tys as ::stecs::Entity>::Borrow<#lifetime>:
+                        ::std::fmt::Debug,
+                )*
+            {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        #(
+                            #ident_ref::#variant_idents(entity) => #variant_debugs,
+                        )*
+                    }
+                }
+            }
+        }
+    });
+
+    let ref_mut_debug = (!ref_mut_derives.contains("Debug")).then(|| {
+        quote! {
+            impl #impl_generics_lifetime ::std::fmt::Debug
+            for #ident_ref_mut #ty_generics_lifetime
+            where
+                #(#where_predicates,)*
+                // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+                #(
+                    for<'__stecs__a> <#variant_tys as ::stecs::Entity>::BorrowMut<#lifetime>:
+                        ::std::fmt::Debug,
+                )*
+            {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        #(
+                            #ident_ref_mut::#variant_idents(entity) => #variant_debugs,
+                        )*
+                    }
+                }
+            }
+        }
+    });
 
     Ok(quote! {
+        #unit_structs
+
         // Entity
 
-        impl ::stecs::Entity for #ident {
-            type Id = #ident_id;
-            type Borrow<#lifetime> = #ident_ref<#lifetime>;
-            type BorrowMut<#lifetime> = #ident_ref_mut<#lifetime>;
-            type WorldData = #ident_world_data;
-            type Fetch<#lifetime> = #ident_ref_fetch<#lifetime>;
-            type FetchMut<#lifetime> = #ident_ref_mut_fetch<#lifetime>;
-            type FetchId<#lifetime> = #ident_id_fetch<#lifetime>;
+        impl #impl_generics ::stecs::Entity for #ident #ty_generics #where_clause {
+            type Id = #ident_id #ty_generics;
+            type Borrow<#lifetime> = #ident_ref #ty_generics_lifetime;
+            type BorrowMut<#lifetime> = #ident_ref_mut #ty_generics_lifetime;
+            type WorldData = #ident_world_data #ty_generics;
+            type Fetch<#lifetime> = #ident_ref_fetch #ty_generics_lifetime;
+            type FetchMut<#lifetime> = #ident_ref_mut_fetch #ty_generics_lifetime;
+            type FetchId<#lifetime> = #ident_id_fetch #ty_generics_lifetime;
         }
 
         // CloneEntityFromRef
 
-        impl ::stecs::CloneEntityFromRef for #ident
+        impl #impl_generics ::stecs::CloneEntityFromRef for #ident #ty_generics
         where
+            #(#where_predicates,)*
             // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
             #(for<'__stecs__a> #variant_tys: ::stecs::CloneEntityFromRef,)*
         {
@@ -105,11 +329,12 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 match entity {
                     #(
                         #ident_ref::#variant_idents(entity) => {
-                            #ident::#variant_idents(
+                            let entity =
                                 <#variant_tys as ::stecs::CloneEntityFromRef>::clone_entity_from_ref(
                                     entity,
-                                ),
-                            )
+                                );
+
+                            #variant_outers
                         }
                     )*
                 }
@@ -119,22 +344,43 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         // EntityVariant
 
         #(
-            impl ::stecs::entity::EntityVariant<#ident> for #variant_tys {
-                fn into_outer(self) -> #ident {
-                    #ident::#variant_idents(self)
+            impl #impl_generics ::stecs::entity::EntityVariant<#ident #ty_generics>
+            for #variant_tys #where_clause {
+                fn into_outer(self) -> #ident #ty_generics {
+                    let entity = self;
+
+                    #variant_outers
                 }
 
-                fn spawn(self, data: &mut #ident_world_data) -> ::stecs::Id<Self> {
+                fn spawn(self, data: &mut #ident_world_data #ty_generics) -> ::stecs::Id<Self> {
                     use ::stecs::WorldData;
 
                     data.#variant_idents.spawn(self)
                 }
 
-                fn id_to_outer(id: Self::Id) -> #ident_id {
+                fn reserve(data: &mut #ident_world_data #ty_generics, additional: usize) {
+                    use ::stecs::WorldData;
+
+                    data.#variant_idents.reserve::<Self>(additional)
+                }
+
+                fn world_data(data: &#ident_world_data #ty_generics) -> &Self::WorldData {
+                    &data.#variant_idents
+                }
+
+                unsafe fn world_data_ptr(
+                    data: *mut #ident_world_data #ty_generics,
+                ) -> *mut Self::WorldData {
+                    ::std::ptr::addr_of_mut!((*data).#variant_idents)
+                }
+
+                fn id_to_outer(id: Self::Id) -> #ident_id #ty_generics {
                     #ident_id::#variant_idents(id)
                 }
 
-                fn try_id_from_outer(id: #ident_id) -> ::std::option::Option<Self::Id> {
+                fn try_id_from_outer(
+                    id: #ident_id #ty_generics,
+                ) -> ::std::option::Option<Self::Id> {
                     if let #ident_id::#variant_idents(id) = id {
                         Some(id)
                     } else {
@@ -144,22 +390,38 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             }
         )*
 
-        impl ::stecs::entity::EntityVariant<#ident> for #ident {
+        impl #impl_generics ::stecs::entity::EntityVariant<#ident #ty_generics>
+        for #ident #ty_generics #where_clause {
             fn into_outer(self) -> Self {
                 self
             }
 
-            fn spawn(self, data: &mut #ident_world_data) -> ::stecs::Id<Self> {
+            fn spawn(self, data: &mut #ident_world_data #ty_generics) -> ::stecs::Id<Self> {
                 use ::stecs::WorldData;
 
                 match self {
                     #(
-                        #ident::#variant_idents(entity) =>
-                            data.#variant_idents.spawn(entity).to_outer(),
+                        #variant_pats => data.#variant_idents.spawn(#variant_inners).to_outer(),
                     )*
                 }
             }
 
+            fn reserve(data: &mut #ident_world_data #ty_generics, additional: usize) {
+                use ::stecs::WorldData;
+
+                #(data.#variant_idents.reserve::<#variant_tys>(additional);)*
+            }
+
+            fn world_data(data: &#ident_world_data #ty_generics) -> &Self::WorldData {
+                data
+            }
+
+            unsafe fn world_data_ptr(
+                data: *mut #ident_world_data #ty_generics,
+            ) -> *mut Self::WorldData {
+                data
+            }
+
             fn id_to_outer(id: Self::Id) -> Self::Id {
                 id
             }
@@ -171,7 +433,298 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
 
         // Id
 
+        // NOTE: We implement the traits of `Id` by hand, since `derive` would
+        // require our generic parameters to implement them as well.
         #[allow(non_camel_case_types)]
+        #id_derives
+        #vis enum #ident_id #impl_generics #where_clause {
+            #(#variant_idents(<#variant_tys as ::stecs::Entity>::Id),)*
+        }
+
+        impl #impl_generics #ident_id #ty_generics #where_clause {
+            fn __stecs__discriminant(&self) -> u64 {
+                match self {
+                    #(#ident_id::#variant_idents(_) => #discriminants,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::clone::Clone for #ident_id #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::std::marker::Copy for #ident_id #ty_generics #where_clause {}
+
+        impl #impl_generics ::std::fmt::Debug for #ident_id #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #(
+                        #ident_id::#variant_idents(id) => f
+                            .debug_tuple(::std::stringify!(#variant_idents))
+                            .field(id)
+                            .finish(),
+                    )*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::cmp::PartialEq for #ident_id #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    #(
+                        (#ident_id::#variant_idents(id), #ident_id::#variant_idents(other)) => {
+                            id == other
+                        }
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => false,
+                }
+            }
+        }
+
+        impl #impl_generics ::std::cmp::Eq for #ident_id #ty_generics #where_clause {}
+
+        impl #impl_generics ::std::cmp::PartialOrd for #ident_id #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> ::std::option::Option<::std::cmp::Ordering> {
+                ::std::option::Option::Some(::std::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl #impl_generics ::std::cmp::Ord for #ident_id #ty_generics #where_clause {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                match (self, other) {
+                    #(
+                        (#ident_id::#variant_idents(id), #ident_id::#variant_idents(other)) => {
+                            ::std::cmp::Ord::cmp(id, other)
+                        }
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => ::std::cmp::Ord::cmp(
+                        &self.__stecs__discriminant(),
+                        &other.__stecs__discriminant(),
+                    ),
+                }
+            }
+        }
+
+        impl #impl_generics ::std::hash::Hash for #ident_id #ty_generics #where_clause {
+            fn hash<__stecs__H: ::std::hash::Hasher>(&self, state: &mut __stecs__H) {
+                ::std::hash::Hash::hash(&self.__stecs__discriminant(), state);
+
+                match self {
+                    #(#ident_id::#variant_idents(id) => ::std::hash::Hash::hash(id, state),)*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #ident_id #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #(
+                        #ident_id::#variant_idents(id) => ::stecs::entity::NamedId::fmt_named(
+                            id,
+                            ::std::stringify!(#variant_idents),
+                            f,
+                        ),
+                    )*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #ident_id #ty_generics #where_clause {
+            type Err = ::stecs::entity::ParseIdError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                ::stecs::entity::NamedId::parse_named(s, "")
+                    .ok_or_else(|| ::stecs::entity::ParseIdError::new(s))
+            }
+        }
+
+        // Enum ids name their variant themselves, so `name` is ignored.
+        impl #impl_generics ::stecs::entity::NamedId for #ident_id #ty_generics #where_clause {
+            fn fmt_named(
+                &self,
+                _: &str,
+                f: &mut ::std::fmt::Formatter<'_>,
+            ) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(self, f)
+            }
+
+            fn parse_named(s: &str, _: &str) -> ::std::option::Option<Self> {
+                #(
+                    if let ::std::option::Option::Some(id) = ::stecs::entity::NamedId::parse_named(
+                        s,
+                        ::std::stringify!(#variant_idents),
+                    ) {
+                        return ::std::option::Option::Some(#ident_id::#variant_idents(id));
+                    }
+                )*
+
+                ::std::option::Option::None
+            }
+        }
+
+        impl #impl_generics ::stecs::entity::IdBits for #ident_id #ty_generics #where_clause {
+            const VARIANT_BITS: u32 = {
+                let mut inner_bits = 0;
+                #(
+                    let bits =
+                        <<#variant_tys as ::stecs::Entity>::Id as ::stecs::entity::IdBits>
+                            ::VARIANT_BITS;
+                    if bits > inner_bits {
+                        inner_bits = bits;
+                    }
+                )*
+
+                inner_bits + #discriminant_bits
+            };
+
+            fn to_bits(self) -> u64 {
+                let shift = 32 - Self::VARIANT_BITS;
+
+                let (discriminant, bits, inner_bits) = match self {
+                    #(
+                        #ident_id::#variant_idents(id) => (
+                            #discriminants,
+                            ::stecs::entity::IdBits::to_bits(id),
+                            <<#variant_tys as ::stecs::Entity>::Id as ::stecs::entity::IdBits>
+                                ::VARIANT_BITS,
+                        ),
+                    )*
+                };
+
+                // The variant has already checked that its slot leaves room
+                // for its own variant bits. The remaining bits that we reserve
+                // have to be free as well, not only those of our discriminant,
+                // so that the slot fits next to all of our variant bits.
+                let mask = ((1u64 << (Self::VARIANT_BITS - inner_bits)) - 1) << shift;
+
+                ::std::assert!(
+                    bits & mask == 0,
+                    "Slot is too large for encoding the variant in the Id bits",
+                );
+
+                bits | (discriminant << shift)
+            }
+
+            fn from_bits(bits: u64) -> ::std::option::Option<Self> {
+                let shift = 32 - Self::VARIANT_BITS;
+                let mask = ((1u64 << #discriminant_bits) - 1) << shift;
+
+                let discriminant = (bits & mask) >> shift;
+                let bits = bits & !mask;
+
+                match discriminant {
+                    #(
+                        #discriminants => {
+                            let inner_bits =
+                                <<#variant_tys as ::stecs::Entity>::Id as ::stecs::entity::IdBits>
+                                    ::VARIANT_BITS;
+                            let mask = ((1u64 << (Self::VARIANT_BITS - inner_bits)) - 1) << shift;
+
+                            // Reject slots that `to_bits` would not produce.
+                            if bits & mask != 0 {
+                                return ::std::option::Option::None;
+                            }
+
+                            ::stecs::entity::IdBits::from_bits(bits)
+                                .map(#ident_id::#variant_idents)
+                        }
+                    )*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        // Ref
+
+        #[allow(non_camel_case_types)]
+        #ref_derives
+        #vis enum #ident_ref #impl_generics_lifetime #where_clause {
+            #(#variant_idents(<#variant_tys as ::stecs::Entity>::Borrow<#lifetime>),)*
+        }
+
+        impl #impl_generics_lifetime ::std::clone::Clone
+        for #ident_ref #ty_generics_lifetime #where_clause {
+            fn clone(&self) -> Self {
+                match self {
+                    #(
+                        #ident_ref::#variant_idents(entity) => {
+                            #ident_ref::#variant_idents(::std::clone::Clone::clone(entity))
+                        }
+                    )*
+                }
+            }
+        }
+
+        #ref_debug
+
+        // RefMut
+
+        #[allow(non_camel_case_types)]
+        #ref_mut_derives
+        #vis enum #ident_ref_mut #impl_generics_lifetime #where_clause {
+            #(#variant_idents(<#variant_tys as ::stecs::Entity>::BorrowMut<#lifetime>),)*
+        }
+
+        #ref_mut_debug
+
+        // EntityDiff
+
+        impl #impl_generics ::stecs::diff::EntityDiff for #ident #ty_generics
+        where
+            #(#where_predicates,)*
+            // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+            #(for<'__stecs__a> #variant_tys: ::stecs::diff::EntityDiff,)*
+        {
+            type Diff = #ident_diff<#(<#variant_tys as ::stecs::diff::EntityDiff>::Diff,)*>;
+
+            fn diff(
+                old: Self::Borrow<'_>,
+                new: Self::Borrow<'_>,
+            ) -> ::std::option::Option<Self::Diff> {
+                match (old, new) {
+                    #(
+                        (#ident_ref::#variant_idents(old), #ident_ref::#variant_idents(new)) => {
+                            <#variant_tys as ::stecs::diff::EntityDiff>::diff(old, new)
+                                .map(#ident_diff::#variant_idents)
+                        }
+                    )*
+                    _ => panic!("Incompatible Ref variants in `diff`"),
+                }
+            }
+
+            fn apply_diff(entity: Self::BorrowMut<'_>, diff: Self::Diff) {
+                match (entity, diff) {
+                    #(
+                        (#ident_ref_mut::#variant_idents(entity), #ident_diff::#variant_idents(diff)) => {
+                            <#variant_tys as ::stecs::diff::EntityDiff>::apply_diff(entity, diff)
+                        }
+                    )*
+                    _ => panic!("Incompatible RefMut and Diff variants in `apply_diff`"),
+                }
+            }
+        }
+
+        // Diff
+
+        #[allow(non_camel_case_types)]
+        #diff_derives
+        #vis enum #ident_diff<#(#diff_type_params,)*> {
+            #(#variant_idents(#diff_type_params),)*
+        }
+
+        // Kind
+
+        #[doc = ::std::concat!(
+            "The variants of [`", ::std::stringify!(#ident), "`], without their payloads.",
+        )]
+        ///
+        /// Always derives `Clone`, `Copy`, `Debug`, `PartialEq`, `Eq`,
+        /// `PartialOrd`, `Ord` and `Hash`. Further traits can be derived with
+        /// `#[stecs(derive_kind(...))]`.
         #[derive(
             ::std::clone::Clone,
             ::std::marker::Copy,
@@ -182,25 +735,37 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             ::std::cmp::Ord,
             ::std::hash::Hash,
         )]
-        #id_derives
-        #vis enum #ident_id {
-            #(#variant_idents(<#variant_tys as ::stecs::Entity>::Id),)*
+        #kind_derives
+        #vis enum #ident_kind {
+            #(#variant_idents,)*
         }
 
-        // Ref
+        impl #ident_kind {
+            /// All kinds, in declaration order.
+            #vis const ALL: [Self; #num_variants] = [#(Self::#variant_idents,)*];
 
-        #[allow(non_camel_case_types)]
-        #[derive(::std::clone::Clone)]
-        #ref_derives
-        #vis enum #ident_ref<#lifetime> {
-            #(#variant_idents(<#variant_tys as ::stecs::Entity>::Borrow<#lifetime>),)*
+            /// Returns the name of the variant, e.g. `"Player"`.
+            #vis fn name(self) -> &'static str {
+                match self {
+                    #(Self::#variant_idents => ::std::stringify!(#variant_idents),)*
+                }
+            }
         }
 
-        // RefMut
+        impl ::std::fmt::Display for #ident_kind {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
 
-        #[allow(non_camel_case_types)]
-        #vis enum #ident_ref_mut<#lifetime> {
-            #(#variant_idents(<#variant_tys as ::stecs::Entity>::BorrowMut<#lifetime>),)*
+        impl #impl_generics ::stecs::entity::EntityEnum for #ident #ty_generics #where_clause {
+            type Kind = #ident_kind;
+
+            fn kind(id: Self::Id) -> Self::Kind {
+                match id {
+                    #(#ident_id::#variant_idents(_) => #ident_kind::#variant_idents,)*
+                }
+            }
         }
 
         // WorldData
@@ -208,36 +773,45 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         // TODO: Consider exposing the `WorldData` struct. In this case, convert
         // field names to snake case first.
         #[allow(non_snake_case, non_camel_case_types)]
-        #[derive(::std::default::Default)]
         #world_data_derives
-        #vis struct #ident_world_data {
+        #vis struct #ident_world_data #impl_generics #where_clause {
             #(#variant_idents: <#variant_tys as ::stecs::Entity>::WorldData,)*
         }
 
-        impl ::stecs::WorldData for #ident_world_data {
-            type Entity = #ident;
-            type Fetch<'w, F: ::stecs::query::fetch::Fetch + 'w> = #ident_world_fetch<'w, F>;
+        impl #impl_generics ::std::default::Default
+        for #ident_world_data #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#variant_idents: ::std::default::Default::default(),)*
+                }
+            }
+        }
+
+        impl #impl_generics ::stecs::WorldData for #ident_world_data #ty_generics #where_clause {
+            type Entity = #ident #ty_generics;
+            type Fetch<#lifetime, #type_param: ::stecs::query::fetch::Fetch + #lifetime> =
+                #ident_world_fetch #ty_generics_fetch;
 
-            fn spawn<E>(&mut self, entity: E) -> ::stecs::Id<E>
+            fn spawn<__stecs__E>(&mut self, entity: __stecs__E) -> ::stecs::Id<__stecs__E>
             where
-                E: ::stecs::entity::EntityVariant<#ident>,
+                __stecs__E: ::stecs::entity::EntityVariant<Self::Entity>,
             {
-                E::spawn(entity, self)
+                __stecs__E::spawn(entity, self)
             }
 
-            fn despawn<E>(
+            fn despawn<__stecs__E>(
                 &mut self,
-                id: ::stecs::Id<E>,
+                id: ::stecs::Id<__stecs__E>,
             ) -> ::std::option::Option<Self::Entity>
             where
-                E: ::stecs::entity::EntityVariant<Self::Entity>,
+                __stecs__E: ::stecs::entity::EntityVariant<Self::Entity>,
             {
                 match id.to_outer().get() {
                     #(
                         #ident_id::#variant_idents(id) => {
                             self.#variant_idents
                                 .despawn(::stecs::Id::<#variant_tys>::new(id))
-                                .map(|entity| #ident::#variant_idents(entity))
+                                .map(|entity| #variant_outers)
                         }
                     )*
                 }
@@ -250,10 +824,10 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             ) -> ::std::option::Option<Self::Entity> {
                 match (id.get(), entity) {
                     #(
-                        (#ident_id::#variant_idents(id), #ident::#variant_idents(entity)) => {
+                        (#ident_id::#variant_idents(id), #variant_pats) => {
                             self.#variant_idents
-                                .spawn_at(::stecs::Id::new(id), entity)
-                                .map(#ident::#variant_idents)
+                                .spawn_at(::stecs::Id::new(id), #variant_inners)
+                                .map(|entity| #variant_outers)
                         }
                     )*
                     _ => panic!("Incompatible Id and Entity variants in `spawn_at`"),
@@ -271,21 +845,185 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 }
             }
 
-            fn fetch<'w, F>(&'w self) -> Self::Fetch<'w, F>
+            fn len(&self) -> usize {
+                let mut len = 0;
+                #(len += self.#variant_idents.len();)*
+
+                len
+            }
+
+            fn clear(&mut self) {
+                #(self.#variant_idents.clear();)*
+            }
+
+            fn reserve<__stecs__E>(&mut self, additional: usize)
+            where
+                __stecs__E: ::stecs::entity::EntityVariant<Self::Entity>,
+            {
+                __stecs__E::reserve(self, additional)
+            }
+
+            fn shrink_to_fit(&mut self) {
+                #(self.#variant_idents.shrink_to_fit();)*
+            }
+
+            fn defragment(&mut self) {
+                #(self.#variant_idents.defragment();)*
+            }
+
+            fn dyn_column(
+                &self,
+                id: ::stecs::Id<Self::Entity>,
+                type_id: ::std::any::TypeId,
+            ) -> ::std::option::Option<(&dyn ::stecs::dynamic::AnyColumn, usize)> {
+                match id.get() {
+                    #(
+                        #ident_id::#variant_idents(id) => {
+                            self.#variant_idents.dyn_column(::stecs::Id::new(id), type_id)
+                        }
+                    )*
+                }
+            }
+
+            fn dyn_archetypes<'w>(
+                &'w self,
+                type_ids: &'w [::std::any::TypeId],
+            ) -> ::std::boxed::Box<
+                dyn ::std::iter::Iterator<Item = ::stecs::dynamic::DynArchetype<'w, Self::Entity>>
+                    + 'w,
+            > {
+                ::std::boxed::Box::new(
+                    ::std::iter::empty()
+                    #(
+                        .chain(self.#variant_idents.dyn_archetypes(type_ids).map(|archetype| {
+                            archetype.map_id(|id| {
+                                ::stecs::Id::new(#ident_id::#variant_idents(id.get()))
+                            })
+                        }))
+                    )*
+                )
+            }
+
+            fn validate(&self) -> ::std::result::Result<(), ::stecs::validate::ValidationError> {
+                #(self.#variant_idents.validate()?;)*
+
+                ::std::result::Result::Ok(())
+            }
+
+            fn memory_stats(&self) -> ::stecs::stats::MemoryStats {
+                let mut archetypes = ::std::vec::Vec::new();
+
+                #(
+                    archetypes.extend(
+                        self.#variant_idents.memory_stats().archetypes.into_iter().map(
+                            |mut archetype| {
+                                archetype.variant.insert(0, ::std::stringify!(#variant_idents));
+                                archetype
+                            },
+                        ),
+                    );
+                )*
+
+                ::stecs::stats::MemoryStats { archetypes }
+            }
+
+            fn describe(&self) -> ::stecs::layout::EntityLayout {
+                ::stecs::layout::EntityLayout {
+                    name: ::std::stringify!(#ident),
+                    type_name: ::std::any::type_name::<Self::Entity>(),
+                    len: ::stecs::WorldData::len(self),
+                    kind: ::stecs::layout::EntityLayoutKind::Enum {
+                        variants: ::std::vec![
+                            #(
+                                (
+                                    ::std::stringify!(#variant_idents),
+                                    self.#variant_idents.describe(),
+                                ),
+                            )*
+                        ],
+                    },
+                }
+            }
+
+            unsafe fn drain_filter<'w, __stecs__F, __stecs__P, __stecs__O>(
+                &mut self,
+                predicate: &mut __stecs__P,
+                out: &mut __stecs__O,
+            )
             where
-                F: ::stecs::query::fetch::Fetch + 'w,
+                __stecs__F: ::stecs::query::fetch::Fetch + 'w,
+                __stecs__P: FnMut(__stecs__F::Item<'w>) -> bool,
+                __stecs__O: FnMut(::stecs::Id<Self::Entity>, Self::Entity),
+            {
+                // Safety: Forwarded to the caller.
+                #(
+                    unsafe {
+                        self.#variant_idents.drain_filter::<__stecs__F, _, _>(
+                            predicate,
+                            &mut |id, entity| {
+                                out(
+                                    ::stecs::Id::new(#ident_id::#variant_idents(id.get())),
+                                    #variant_outers,
+                                )
+                            },
+                        );
+                    }
+                )*
+            }
+
+            fn fetch<'w, __stecs__F>(&'w self) -> Self::Fetch<'w, __stecs__F>
+            where
+                __stecs__F: ::stecs::query::fetch::Fetch + 'w,
             {
                 #ident_world_fetch {
-                    #(#variant_idents: self.#variant_idents.fetch::<F>(),)*
+                    #(#variant_idents: self.#variant_idents.fetch::<__stecs__F>(),)*
                 }
             }
         }
 
+        impl #impl_generics ::std::iter::IntoIterator
+        for #ident_world_data #ty_generics #where_clause {
+            type Item = (::stecs::Id<#ident #ty_generics>, #ident #ty_generics);
+            type IntoIter = #world_data_into_iter;
+
+            fn into_iter(self) -> Self::IntoIter {
+                let iter = ::std::iter::empty();
+                #(
+                    let iter = ::std::iter::Iterator::chain(
+                        iter,
+                        ::stecs::world::EntityWorldIntoIter::new(self.#variant_idents),
+                    );
+                )*
+
+                iter
+            }
+        }
+
+        impl #impl_generics ::stecs::snapshot::CloneFromChanged for #ident_world_data #ty_generics
+        where
+            #(#where_predicates,)*
+            // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+            #(
+                for<'__stecs__a> <#variant_tys as ::stecs::Entity>::WorldData:
+                    ::stecs::snapshot::CloneFromChanged,
+            )*
+        {
+            fn clone_from_changed(&mut self, source: &Self) {
+                #(
+                    ::stecs::snapshot::CloneFromChanged::clone_from_changed(
+                        &mut self.#variant_idents,
+                        &source.#variant_idents,
+                    );
+                )*
+            }
+        }
+
         // WorldFetch
 
         #[allow(non_snake_case, non_camel_case_types)]
-        #vis struct #ident_world_fetch<#lifetime, #type_param>
+        #vis struct #ident_world_fetch #impl_generics_fetch
         where
+            #(#where_predicates,)*
             #type_param: ::stecs::query::fetch::Fetch + #lifetime,
         {
             #(
@@ -297,9 +1035,9 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             )*
         }
 
-        impl<#lifetime, #type_param> ::std::clone::Clone
-        for #ident_world_fetch<#lifetime, #type_param>
+        impl #impl_generics_fetch ::std::clone::Clone for #ident_world_fetch #ty_generics_fetch
         where
+            #(#where_predicates,)*
             #type_param: ::stecs::query::fetch::Fetch + #lifetime,
         {
             fn clone(&self) -> Self {
@@ -307,21 +1045,26 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             }
         }
 
-        impl<#lifetime, #type_param> ::std::marker::Copy
-        for #ident_world_fetch<#lifetime, #type_param>
+        impl #impl_generics_fetch ::std::marker::Copy for #ident_world_fetch #ty_generics_fetch
         where
+            #(#where_predicates,)*
             #type_param: ::stecs::query::fetch::Fetch + #lifetime,
         {}
 
-        impl<'w, F> ::stecs::world::WorldFetch<'w, F> for #ident_world_fetch<'w, F>
+        impl #impl_generics_fetch ::stecs::world::WorldFetch<#lifetime, #type_param>
+        for #ident_world_fetch #ty_generics_fetch
         where
-            F: ::stecs::query::fetch::Fetch,
+            #(#where_predicates,)*
+            #type_param: ::stecs::query::fetch::Fetch,
         {
-            type Data = #ident_world_data;
+            type Data = #ident_world_data #ty_generics;
             type Iter = #world_fetch_iter;
 
             #[inline]
-            unsafe fn get<'a>(&self, id: #ident_id) -> ::std::option::Option<F::Item<'a>> {
+            unsafe fn get<'a>(
+                &self,
+                id: #ident_id #ty_generics,
+            ) -> ::std::option::Option<#type_param::Item<'a>> {
                 // Safety: TODO
                 match id {
                     #(#ident_id::#variant_idents(id) => unsafe { self.#variant_idents.get(id) },)*
@@ -348,17 +1091,27 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         // IdFetch
 
         #[allow(non_camel_case_types)]
-        #[derive(::std::clone::Clone, ::std::marker::Copy)]
-        #vis enum #ident_id_fetch<'w> {
-            #(#variant_idents(<#variant_tys as ::stecs::entity::Entity>::FetchId<'w>),)*
+        #vis enum #ident_id_fetch #impl_generics_lifetime #where_clause {
+            #(#variant_idents(<#variant_tys as ::stecs::entity::Entity>::FetchId<#lifetime>),)*
+        }
+
+        impl #impl_generics_lifetime ::std::clone::Clone
+        for #ident_id_fetch #ty_generics_lifetime #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
         }
 
-        unsafe impl<'w> ::stecs::query::fetch::Fetch for #ident_id_fetch<'w> {
-            type Item<'a> = ::stecs::Id<#ident> where Self: 'a;
+        impl #impl_generics_lifetime ::std::marker::Copy
+        for #ident_id_fetch #ty_generics_lifetime #where_clause {}
+
+        unsafe impl #impl_generics_lifetime ::stecs::query::fetch::Fetch
+        for #ident_id_fetch #ty_generics_lifetime #where_clause {
+            type Item<#lifetime2> = ::stecs::Id<#ident #ty_generics> where Self: #lifetime2;
 
-            fn new<A: ::stecs::entity::Columns>(
+            fn new<__stecs__A: ::stecs::entity::Columns>(
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
-                columns: &A,
+                columns: &__stecs__A,
             ) -> ::std::option::Option<Self> {
                 let mut result = None;
                 #(
@@ -376,9 +1129,9 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 }
             }
 
-            unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+            unsafe fn get<#lifetime2>(&self, index: usize) -> Self::Item<#lifetime2>
             where
-                Self: 'a,
+                Self: #lifetime2,
             {
                 ::stecs::Id::new(match self {
                     #(
@@ -392,23 +1145,41 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         // RefFetch
 
         #[allow(non_camel_case_types)]
-        #[derive(::std::clone::Clone, ::std::marker::Copy)]
-        #vis enum #ident_ref_fetch<'w> {
-            #(#variant_idents(<#variant_tys as ::stecs::entity::Entity>::Fetch<'w>),)*
+        #vis enum #ident_ref_fetch #impl_generics_lifetime #where_clause {
+            #(#variant_idents(<#variant_tys as ::stecs::entity::Entity>::Fetch<#lifetime>),)*
         }
 
-        unsafe impl<'w> ::stecs::query::fetch::Fetch for #ident_ref_fetch<'w> {
-            type Item<'a> = #ident_ref<'a> where Self: 'a;
+        impl #impl_generics_lifetime ::std::clone::Clone
+        for #ident_ref_fetch #ty_generics_lifetime #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics_lifetime ::std::marker::Copy
+        for #ident_ref_fetch #ty_generics_lifetime #where_clause {}
+
+        unsafe impl #impl_generics_lifetime ::stecs::query::fetch::Fetch
+        for #ident_ref_fetch #ty_generics_lifetime #where_clause {
+            type Item<#lifetime2> = #ident_ref #ty_generics_lifetime2 where Self: #lifetime2;
 
-            fn new<A: ::stecs::entity::Columns>(
+            fn new<__stecs__A: ::stecs::entity::Columns>(
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
-                columns: &A,
+                columns: &__stecs__A,
             ) -> ::std::option::Option<Self> {
+                // The fetches of struct entities also match entities that they
+                // are flattened into, so we use the exact id fetch to check
+                // that the columns belong to the variant.
                 let mut result = None;
                 #(
-                    result = result.or_else(|| ::stecs::query::fetch::Fetch::new(ids, columns).map(
-                        #ident_ref_fetch::#variant_idents,
-                    ));
+                    result = result.or_else(|| {
+                        <<#variant_tys as ::stecs::Entity>::FetchId<#lifetime> as
+                            ::stecs::query::fetch::Fetch>::new(ids, columns)?;
+
+                        ::stecs::query::fetch::Fetch::new(ids, columns).map(
+                            #ident_ref_fetch::#variant_idents,
+                        )
+                    });
                 )*
 
                 result
@@ -420,9 +1191,9 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 }
             }
 
-            unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+            unsafe fn get<#lifetime2>(&self, index: usize) -> Self::Item<#lifetime2>
             where
-                Self: 'a,
+                Self: #lifetime2,
             {
                 match self {
                     #(
@@ -434,38 +1205,62 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             }
         }
 
-        unsafe impl<'q> ::stecs::Query for #ident_ref<'q> {
-            type Fetch<'w> = #ident_ref_fetch<'w>;
+        unsafe impl #impl_generics_lifetime ::stecs::Query
+        for #ident_ref #ty_generics_lifetime #where_clause {
+            type Fetch<#lifetime2> = #ident_ref_fetch #ty_generics_lifetime2;
 
             fn for_each_borrow(mut f: impl FnMut(::std::any::TypeId, bool)) {
-                #(<#variant_tys as ::stecs::Entity>::Borrow::<'q>::for_each_borrow(&mut f);)*
+                #(
+                    <#variant_tys as ::stecs::Entity>::Borrow::<#lifetime>::for_each_borrow(
+                        &mut f,
+                    );
+                )*
             }
         }
 
-        unsafe impl<'q> ::stecs::QueryShared for #ident_ref<'q> {}
+        unsafe impl #impl_generics_lifetime ::stecs::QueryShared
+        for #ident_ref #ty_generics_lifetime #where_clause {}
 
         // RefMutFetch
 
         #[allow(non_camel_case_types)]
-        #[derive(::std::clone::Clone, ::std::marker::Copy)]
-        #vis enum #ident_ref_mut_fetch<'w> {
+        #vis enum #ident_ref_mut_fetch #impl_generics_lifetime #where_clause {
             #(
-                #variant_idents(<#variant_tys as ::stecs::entity::Entity>::FetchMut<'w>),
+                #variant_idents(<#variant_tys as ::stecs::entity::Entity>::FetchMut<#lifetime>),
             )*
         }
 
-        unsafe impl<'w> ::stecs::query::fetch::Fetch for #ident_ref_mut_fetch<'w> {
-            type Item<'a> = #ident_ref_mut<'a> where Self: 'a;
+        impl #impl_generics_lifetime ::std::clone::Clone
+        for #ident_ref_mut_fetch #ty_generics_lifetime #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics_lifetime ::std::marker::Copy
+        for #ident_ref_mut_fetch #ty_generics_lifetime #where_clause {}
+
+        unsafe impl #impl_generics_lifetime ::stecs::query::fetch::Fetch
+        for #ident_ref_mut_fetch #ty_generics_lifetime #where_clause {
+            type Item<#lifetime2> = #ident_ref_mut #ty_generics_lifetime2 where Self: #lifetime2;
 
-            fn new<A: ::stecs::entity::Columns>(
+            fn new<__stecs__A: ::stecs::entity::Columns>(
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
-                columns: &A,
+                columns: &__stecs__A,
             ) -> ::std::option::Option<Self> {
+                // The fetches of struct entities also match entities that they
+                // are flattened into, so we use the exact id fetch to check
+                // that the columns belong to the variant.
                 let mut result = None;
                 #(
-                    result = result.or_else(|| ::stecs::query::fetch::Fetch::new(ids, columns).map(
-                        #ident_ref_mut_fetch::#variant_idents,
-                    ));
+                    result = result.or_else(|| {
+                        <<#variant_tys as ::stecs::Entity>::FetchId<#lifetime> as
+                            ::stecs::query::fetch::Fetch>::new(ids, columns)?;
+
+                        ::stecs::query::fetch::Fetch::new(ids, columns).map(
+                            #ident_ref_mut_fetch::#variant_idents,
+                        )
+                    });
                 )*
 
                 result
@@ -477,9 +1272,9 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 }
             }
 
-            unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+            unsafe fn get<#lifetime2>(&self, index: usize) -> Self::Item<#lifetime2>
             where
-                Self: 'a,
+                Self: #lifetime2,
             {
                 match self {
                     #(
@@ -490,8 +1285,9 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
             }
         }
 
-        unsafe impl<'q> ::stecs::Query for #ident_ref_mut<'q> {
-            type Fetch<'w> = #ident_ref_mut_fetch<'w>;
+        unsafe impl #impl_generics_lifetime ::stecs::Query
+        for #ident_ref_mut #ty_generics_lifetime #where_clause {
+            type Fetch<#lifetime2> = #ident_ref_mut_fetch #ty_generics_lifetime2;
 
             fn for_each_borrow(mut f: impl FnMut(::std::any::TypeId, bool)) {
                 let mut borrows = ::stecs::fxhash::FxHashSet::default();
@@ -506,7 +1302,7 @@ pub fn derive(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                 // runtime overhead. Is there a way to write this that enables
                 // the compiler to optimize it out when used in `assert_borrow`?
                 #(
-                    <#variant_tys as ::stecs::Entity>::BorrowMut::<'q>::for_each_borrow(
+                    <#variant_tys as ::stecs::Entity>::BorrowMut::<#lifetime>::for_each_borrow(
                         |borrow, _| {
                             borrows.insert(borrow);
                         }
@@ -1,78 +1,151 @@
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{DeriveInput, Result};
+use quote::{quote, quote_spanned};
+use syn::{spanned::Spanned, DeriveInput, Error, Result};
 
 use crate::utils::{
-    associated_ident, generics_with_new_lifetime, get_attr_derives, parse_attr_names, Derives,
+    associated_ident, generics_with_new_lifetime, get_entity_attrs, members_as_idents,
+    parse_attr_names, struct_fields, EntityAttrs,
 };
 
+struct Field<'a> {
+    /// How the field is accessed on the entity and its `Ref`, `RefMut` and
+    /// `Diff` types, which all mirror the shape of the entity struct.
+    member: syn::Member,
+    /// The name of the field in the generated columns and fetches.
+    ident: syn::Ident,
+    ty: &'a syn::Type,
+    flat: bool,
+    tag: bool,
+}
+
 #[derive(Default)]
 struct Fields<'a> {
-    idents: Vec<&'a syn::Ident>,
+    members: Vec<syn::Member>,
+    idents: Vec<syn::Ident>,
     tys: Vec<&'a syn::Type>,
 }
 
-impl<'a> FromIterator<&'a syn::Field> for Fields<'a> {
-    fn from_iter<T: IntoIterator<Item = &'a syn::Field>>(iter: T) -> Self {
+impl<'a, 'b> FromIterator<&'b Field<'a>> for Fields<'a> {
+    fn from_iter<T: IntoIterator<Item = &'b Field<'a>>>(iter: T) -> Self {
         let mut result = Fields::default();
 
         for field in iter {
-            result
-                .idents
-                .push(field.ident.as_ref().expect("Expected named struct"));
-            result.tys.push(&field.ty);
+            result.members.push(field.member.clone());
+            result.idents.push(field.ident.clone());
+            result.tys.push(field.ty);
         }
 
         result
     }
 }
 
-fn split_fields(fields: &syn::FieldsNamed) -> Result<(Fields, Fields)> {
-    let (mut field_comps, mut field_flats) = (Vec::new(), Vec::new());
+fn get_fields(fields: &syn::Fields) -> Result<Vec<Field<'_>>> {
+    let (tys, members) = struct_fields(fields);
+    let idents = members_as_idents(&members);
+
+    fields
+        .iter()
+        .zip(tys)
+        .zip(members.iter().zip(idents))
+        .map(|((field, ty), (member, ident))| {
+            let attrs = parse_attr_names(&field.attrs)?;
+            let flat = attrs.iter().any(|a| a == "flat");
+            let tag = attrs.iter().any(|a| a == "tag");
+
+            if flat && tag {
+                return Err(Error::new_spanned(
+                    field,
+                    "#[stecs(flat)] and #[stecs(tag)] cannot be combined",
+                ));
+            }
 
-    for field in &fields.named {
-        let attrs = parse_attr_names(&field.attrs)?;
+            Ok(Field {
+                member: member.clone(),
+                ident: ident.into_owned(),
+                ty,
+                flat,
+                tag,
+            })
+        })
+        .collect()
+}
 
-        if attrs.iter().any(|a| a == "flat") {
-            &mut field_flats
-        } else {
-            &mut field_comps
-        }
-        .push(field);
+/// Generates the body of a struct definition that has the same shape as the
+/// entity struct. Unit structs get braces, so that they can hold a phantom
+/// field.
+fn struct_body(
+    fields: &syn::Fields,
+    where_clause: Option<&syn::WhereClause>,
+    body: TokenStream2,
+) -> TokenStream2 {
+    match fields {
+        syn::Fields::Unnamed(_) => quote! { (#body) #where_clause; },
+        syn::Fields::Named(_) | syn::Fields::Unit => quote! { #where_clause { #body } },
     }
-
-    Ok((
-        field_comps.into_iter().collect(),
-        field_flats.into_iter().collect(),
-    ))
 }
 
-pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStream2> {
+pub fn derive(input: &DeriveInput, fields: &syn::Fields) -> Result<TokenStream2> {
     let ident = &input.ident;
     let vis = &input.vis;
 
     let ident_columns = associated_ident(ident, "Columns");
+    let ident_columns_into_iter = associated_ident(ident, "ColumnsIntoIter");
+    let ident_diff = associated_ident(ident, "Diff");
     let ident_ref = associated_ident(ident, "Ref");
     let ident_ref_mut = associated_ident(ident, "RefMut");
     let ident_ref_fetch = associated_ident(ident, "RefFetch");
     let ident_ref_mut_fetch = associated_ident(ident, "RefMutFetch");
 
-    let Derives {
+    let EntityAttrs {
+        // Struct entities use `EntityKey` as their id and `Archetype` as their
+        // world data, so there is nothing to derive these traits on. The
+        // attributes are accepted and ignored, as they always have been.
+        id_derives: _,
+        world_data_derives: _,
         columns_derives,
         ref_derives,
-        ..
-    } = get_attr_derives(&input.attrs)?;
+        ref_mut_derives,
+        diff_derives,
+        kind_derives,
+        kind,
+        ordered,
+    } = get_entity_attrs(&input.attrs)?;
+
+    if !kind_derives.is_empty() || kind.is_some() {
+        return Err(Error::new_spanned(
+            ident,
+            "#[stecs(kind = ...)] and #[stecs(derive_kind(...))] are only supported on enums, \
+             since only they have a `Kind`",
+        ));
+    }
 
-    let (
-        Fields {
-            idents: field_comp_idents,
-            tys: field_comp_tys,
-        },
-        Fields {
-            idents: field_flat_idents,
-            tys: field_flat_tys,
-        },
-    ) = split_fields(fields)?;
+    let fields_all = get_fields(fields)?;
+
+    let Fields {
+        members: field_comp_members,
+        idents: field_comp_idents,
+        tys: field_comp_tys,
+    } = fields_all.iter().filter(|field| !field.flat).collect();
+    // Tags are zero-sized components that are not stored in a column. Other
+    // than that, they are treated like components.
+    let Fields {
+        members: field_column_members,
+        idents: field_column_idents,
+        tys: field_column_tys,
+    } = fields_all
+        .iter()
+        .filter(|field| !field.flat && !field.tag)
+        .collect();
+    let Fields {
+        members: field_tag_members,
+        idents: field_tag_idents,
+        tys: field_tag_tys,
+    } = fields_all.iter().filter(|field| field.tag).collect();
+    let Fields {
+        members: field_flat_members,
+        idents: field_flat_idents,
+        tys: field_flat_tys,
+    } = fields_all.iter().filter(|field| field.flat).collect();
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
@@ -86,6 +159,160 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
     let generics_lifetime2 = generics_with_new_lifetime(&input.generics, &lifetime2);
     let (_, ty_generics_lifetime2, _) = generics_lifetime2.split_for_impl();
 
+    let where_predicates: Vec<_> = where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .collect();
+
+    // `Ref`, `RefMut` and `Diff` declare their fields in the original order, so
+    // that tuple entities keep their field indices.
+    let field_prefixes: Vec<_> = fields_all
+        .iter()
+        .map(|field| match &field.member {
+            syn::Member::Named(ident) => quote! { #ident: },
+            syn::Member::Unnamed(_) => quote! {},
+        })
+        .collect();
+    let field_ref_tys: Vec<_> = fields_all
+        .iter()
+        .map(|Field { ty, flat, .. }| {
+            if *flat {
+                quote! { ::stecs::EntityRef<#lifetime, #ty> }
+            } else {
+                quote! { &#lifetime #ty }
+            }
+        })
+        .collect();
+    let field_ref_mut_tys: Vec<_> = fields_all
+        .iter()
+        .map(|Field { ty, flat, .. }| {
+            if *flat {
+                quote! { ::stecs::EntityRefMut<#lifetime, #ty> }
+            } else {
+                quote! { &#lifetime mut #ty }
+            }
+        })
+        .collect();
+    let field_members: Vec<_> = fields_all.iter().map(|field| &field.member).collect();
+    let field_tys: Vec<_> = fields_all.iter().map(|field| field.ty).collect();
+
+    let (phantom_member, phantom_prefix) = match fields {
+        syn::Fields::Unnamed(_) => {
+            let index = syn::Index::from(fields_all.len());
+            (quote! { #index }, quote! {})
+        }
+        syn::Fields::Named(_) | syn::Fields::Unit => {
+            (quote! { __stecs__phantom }, quote! { __stecs__phantom: })
+        }
+    };
+
+    // Tags are not stored, so they are recreated with `Default` when an
+    // entity is moved out. Both requirements are checked with the span of the
+    // field type, so that errors point at the offending tag.
+    let tag_asserts = field_tag_tys.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            const {
+                ::std::assert!(
+                    ::std::mem::size_of::<#ty>() == 0,
+                    "Tag components must be zero-sized",
+                )
+            };
+        }
+    });
+    let tag_defaults: Vec<_> = field_tag_tys
+        .iter()
+        .map(|ty| quote_spanned! {ty.span()=> <#ty as ::std::default::Default>::default() })
+        .collect();
+
+    // The columns do not mention the types of tags, which may use generic
+    // parameters.
+    let tags_phantom_field = (!field_tag_tys.is_empty()).then(|| {
+        quote! {
+            __stecs__tags: ::std::marker::PhantomData<fn() -> (#(#field_tag_tys,)*)>,
+        }
+    });
+    let tags_phantom_init = (!field_tag_tys.is_empty()).then(|| {
+        quote! {
+            __stecs__tags: ::std::marker::PhantomData,
+        }
+    });
+
+    let ref_body = struct_body(
+        fields,
+        where_clause_lifetime,
+        quote! {
+            #(#vis #field_prefixes #field_ref_tys,)*
+            #phantom_prefix ::std::marker::PhantomData<&#lifetime ()>,
+        },
+    );
+    let ref_mut_body = struct_body(
+        fields,
+        where_clause_lifetime,
+        quote! {
+            #(#vis #field_prefixes #field_ref_mut_tys,)*
+            #phantom_prefix ::std::marker::PhantomData<&#lifetime mut ()>,
+        },
+    );
+    let diff_body = struct_body(
+        fields,
+        where_clause,
+        quote! {
+            #(#vis #field_prefixes ::std::option::Option<#field_tys>,)*
+        },
+    );
+
+    let debug_body = match fields {
+        syn::Fields::Unnamed(_) => quote! {
+            f.debug_tuple(::std::stringify!(#ident))
+                #(.field(&self.#field_members))*
+                .finish()
+        },
+        syn::Fields::Named(_) | syn::Fields::Unit => quote! {
+            f.debug_struct(::std::stringify!(#ident))
+                #(.field(::std::stringify!(#field_members), &self.#field_members))*
+                .finish()
+        },
+    };
+
+    let ref_debug = (!ref_derives.contains("Debug")).then(|| {
+        quote! {
+            impl #impl_generics_lifetime ::std::fmt::Debug for #ident_ref #ty_generics_lifetime
+            where
+                #(#where_predicates,)*
+                // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+                #(for<'__stecs__b> #field_comp_tys: ::std::fmt::Debug,)*
+                #(
+                    for<'__stecs__b> ::stecs::EntityRef<#lifetime, #field_flat_tys>:
+                        ::std::fmt::Debug,
+                )*
+            {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    #debug_body
+                }
+            }
+        }
+    });
+
+    let ref_mut_debug = (!ref_mut_derives.contains("Debug")).then(|| {
+        quote! {
+            impl #impl_generics_lifetime ::std::fmt::Debug
+            for #ident_ref_mut #ty_generics_lifetime
+            where
+                #(#where_predicates,)*
+                // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+                #(for<'__stecs__b> #field_comp_tys: ::std::fmt::Debug,)*
+                #(
+                    for<'__stecs__b> ::stecs::EntityRefMut<#lifetime, #field_flat_tys>:
+                        ::std::fmt::Debug,
+                )*
+            {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    #debug_body
+                }
+            }
+        }
+    });
+
     Ok(quote! {
         // Entity
 
@@ -109,10 +336,10 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
         {
             fn clone_entity_from_ref(entity: Self::Borrow<'_>) -> Self {
                 Self {
-                    #(#field_comp_idents: ::std::clone::Clone::clone(entity.#field_comp_idents),)*
-                    #(#field_flat_idents:
+                    #(#field_comp_members: ::std::clone::Clone::clone(entity.#field_comp_members),)*
+                    #(#field_flat_members:
                         <#field_flat_tys as ::stecs::CloneEntityFromRef>::clone_entity_from_ref(
-                            entity.#field_flat_idents,
+                            entity.#field_flat_members,
                         ),
                     )*
                 }
@@ -129,19 +356,94 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
         {
             fn clone_entity_into_ref(&self, target: &mut Self::BorrowMut<'_>) {
                 #(
-                    *target.#field_comp_idents = ::std::clone::Clone::clone(
-                        &self.#field_comp_idents,
+                    *target.#field_comp_members = ::std::clone::Clone::clone(
+                        &self.#field_comp_members,
                     );
                 )*
                 #(
                     <#field_flat_tys as ::stecs::CloneEntityIntoRef>::clone_entity_into_ref(
-                        &self.#field_flat_idents,
-                        &mut target.#field_flat_idents,
+                        &self.#field_flat_members,
+                        &mut target.#field_flat_members,
                     );
                 )*
             }
         }
 
+        // EntityDiff
+
+        impl #impl_generics ::stecs::diff::EntityDiff for #ident #ty_generics #where_clause
+        where
+            // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+            #(
+                for<'__stecs__a> #field_comp_tys:
+                    ::std::clone::Clone + ::std::cmp::PartialEq,
+            )*
+            #(
+                for<'__stecs__a> #field_flat_tys:
+                    ::stecs::diff::EntityDiff
+                    + ::stecs::CloneEntityFromRef
+                    + ::stecs::CloneEntityIntoRef,
+            )*
+        {
+            type Diff = #ident_diff #ty_generics;
+
+            fn diff(
+                old: Self::Borrow<'_>,
+                new: Self::Borrow<'_>,
+            ) -> ::std::option::Option<Self::Diff> {
+                let diff = #ident_diff {
+                    #(
+                        #field_comp_members: if old.#field_comp_members != new.#field_comp_members {
+                            ::std::option::Option::Some(
+                                ::std::clone::Clone::clone(new.#field_comp_members),
+                            )
+                        } else {
+                            ::std::option::Option::None
+                        },
+                    )*
+                    #(
+                        #field_flat_members: <#field_flat_tys as ::stecs::diff::EntityDiff>::diff(
+                            ::std::clone::Clone::clone(&old.#field_flat_members),
+                            ::std::clone::Clone::clone(&new.#field_flat_members),
+                        )
+                        .map(|_| {
+                            <#field_flat_tys as ::stecs::CloneEntityFromRef>::clone_entity_from_ref(
+                                new.#field_flat_members,
+                            )
+                        }),
+                    )*
+                };
+
+                let changed = false
+                    #(|| diff.#field_comp_members.is_some())*
+                    #(|| diff.#field_flat_members.is_some())*;
+
+                changed.then_some(diff)
+            }
+
+            fn apply_diff(mut entity: Self::BorrowMut<'_>, diff: Self::Diff) {
+                #(
+                    if let ::std::option::Option::Some(component) = diff.#field_comp_members {
+                        *entity.#field_comp_members = component;
+                    }
+                )*
+                #(
+                    if let ::std::option::Option::Some(flat) = diff.#field_flat_members {
+                        <#field_flat_tys as ::stecs::CloneEntityIntoRef>::clone_entity_into_ref(
+                            &flat,
+                            &mut entity.#field_flat_members,
+                        );
+                    }
+                )*
+            }
+        }
+
+        // Diff
+
+        #[allow(unused, non_camel_case_types)]
+        #diff_derives
+        #vis struct #ident_diff #impl_generics #diff_body
+
         // EntityVariant
 
         impl #impl_generics ::stecs::entity::EntityVariant<#ident #ty_generics>
@@ -156,6 +458,20 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 data.spawn(self)
             }
 
+            fn reserve(data: &mut Self::WorldData, additional: usize) {
+                use ::stecs::WorldData;
+
+                data.reserve::<Self>(additional)
+            }
+
+            fn world_data(data: &Self::WorldData) -> &Self::WorldData {
+                data
+            }
+
+            unsafe fn world_data_ptr(data: *mut Self::WorldData) -> *mut Self::WorldData {
+                data
+            }
+
             fn id_to_outer(id: Self::Id) -> Self::Id {
                 id
             }
@@ -176,16 +492,18 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
         #[allow(unused, non_camel_case_types)]
         #columns_derives
         #vis struct #ident_columns #impl_generics #where_clause {
-            #(#field_comp_idents: ::stecs::column::Column<#field_comp_tys>,)*
+            #(#field_column_idents: ::stecs::column::Column<#field_column_tys>,)*
             #(#field_flat_idents: ::stecs::entity::EntityColumns<#field_flat_tys>,)*
+            #tags_phantom_field
         }
 
         impl #impl_generics ::std::default::Default
         for #ident_columns #ty_generics #where_clause {
             fn default() -> Self {
                 Self {
-                    #(#field_comp_idents: ::std::default::Default::default(),)*
+                    #(#field_column_idents: ::std::default::Default::default(),)*
                     #(#field_flat_idents: ::std::default::Default::default(),)*
+                    #tags_phantom_init
                 }
             }
         }
@@ -193,6 +511,9 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
         impl #impl_generics ::stecs::entity::Columns
         for #ident_columns #ty_generics #where_clause {
             type Entity = #ident #ty_generics;
+            type IntoIter = #ident_columns_into_iter #ty_generics;
+
+            const ORDERED: bool = #ordered;
 
             fn column<__stecs__C: ::stecs::Component>(
                 &self,
@@ -200,7 +521,7 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 let mut result = ::std::option::Option::None;
                 #(
                     result = result.or_else(||
-                        ::stecs::column::downcast_ref(&self.#field_comp_idents)
+                        ::stecs::column::downcast_ref(&self.#field_column_idents)
                     );
                 )*
                 #(
@@ -212,15 +533,158 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 result
             }
 
+            fn flat_columns<__stecs__T: ::stecs::entity::Columns>(
+                &self,
+            ) -> ::std::option::Option<&__stecs__T> {
+                let mut result = ::stecs::entity::downcast_columns_ref(self);
+                #(
+                    result = result.or_else(||
+                        self.#field_flat_idents.flat_columns::<__stecs__T>()
+                    );
+                )*
+
+                result
+            }
+
+            fn has_tag<__stecs__C: ::stecs::Component>() -> bool {
+                false
+                    #(
+                        || ::std::any::TypeId::of::<__stecs__C>()
+                            == ::std::any::TypeId::of::<#field_tag_tys>()
+                    )*
+                    #(
+                        || <
+                            ::stecs::entity::EntityColumns<#field_flat_tys>
+                            as ::stecs::entity::Columns
+                        >::has_tag::<__stecs__C>()
+                    )*
+            }
+
+            fn dyn_column(
+                &self,
+                type_id: ::std::any::TypeId,
+            ) -> ::std::option::Option<&dyn ::stecs::dynamic::AnyColumn> {
+                #(
+                    if type_id == ::std::any::TypeId::of::<#field_column_tys>() {
+                        return ::std::option::Option::Some(&self.#field_column_idents);
+                    }
+                )*
+                #(
+                    if let ::std::option::Option::Some(column) =
+                        self.#field_flat_idents.dyn_column(type_id)
+                    {
+                        return ::std::option::Option::Some(column);
+                    }
+                )*
+
+                ::std::option::Option::None
+            }
+
+            fn for_each_column(
+                &self,
+                f: &mut dyn FnMut(&str, &dyn ::stecs::dynamic::AnyColumn),
+            ) {
+                #(f(::std::stringify!(#field_column_members), &self.#field_column_idents);)*
+                #(
+                    self.#field_flat_idents.for_each_column(&mut |field, column| {
+                        f(
+                            &::std::format!(
+                                "{}.{}",
+                                ::std::stringify!(#field_flat_members),
+                                field,
+                            ),
+                            column,
+                        )
+                    });
+                )*
+            }
+
             fn push(&mut self, entity: Self::Entity) {
-                #(self.#field_comp_idents.push(entity.#field_comp_idents);)*
-                #(self.#field_flat_idents.push(entity.#field_flat_idents);)*
+                #(#tag_asserts)*
+                #(self.#field_column_idents.push(entity.#field_column_members);)*
+                #(self.#field_flat_idents.push(entity.#field_flat_members);)*
             }
 
             fn remove(&mut self, index: usize) -> Self::Entity {
                 #ident {
-                    #(#field_comp_idents: self.#field_comp_idents.remove(index),)*
-                    #(#field_flat_idents: self.#field_flat_idents.remove(index),)*
+                    #(#field_column_members: self.#field_column_idents.remove(index),)*
+                    #(#field_tag_members: #tag_defaults,)*
+                    #(#field_flat_members: self.#field_flat_idents.remove(index),)*
+                }
+            }
+
+            fn shift_remove(&mut self, index: usize) -> Self::Entity {
+                #ident {
+                    #(#field_column_members: self.#field_column_idents.shift_remove(index),)*
+                    #(#field_tag_members: #tag_defaults,)*
+                    #(#field_flat_members: self.#field_flat_idents.shift_remove(index),)*
+                }
+            }
+
+            fn swap(&mut self, a: usize, b: usize) {
+                #(self.#field_column_idents.swap(a, b);)*
+                #(self.#field_flat_idents.swap(a, b);)*
+            }
+
+            fn truncate(&mut self, len: usize) {
+                #(self.#field_column_idents.truncate(len);)*
+                #(self.#field_flat_idents.truncate(len);)*
+            }
+
+            fn reserve(&mut self, additional: usize) {
+                #(self.#field_column_idents.reserve(additional);)*
+                #(self.#field_flat_idents.reserve(additional);)*
+            }
+
+            fn shrink_to_fit(&mut self) {
+                #(self.#field_column_idents.shrink_to_fit();)*
+                #(self.#field_flat_idents.shrink_to_fit();)*
+            }
+
+            fn describe(len: usize) -> ::stecs::layout::EntityLayout {
+                ::stecs::layout::EntityLayout {
+                    name: ::std::stringify!(#ident),
+                    type_name: ::std::any::type_name::<Self::Entity>(),
+                    len,
+                    kind: ::stecs::layout::EntityLayoutKind::Struct {
+                        components: ::std::vec![
+                            #(
+                                ::stecs::layout::ComponentLayout::new::<#field_column_tys>(
+                                    ::std::stringify!(#field_column_members),
+                                ),
+                            )*
+                        ],
+                        tags: ::std::vec![
+                            #(
+                                ::stecs::layout::ComponentLayout::new::<#field_tag_tys>(
+                                    ::std::stringify!(#field_tag_members),
+                                ),
+                            )*
+                        ],
+                        flattened: ::std::vec![
+                            #(
+                                (
+                                    ::std::stringify!(#field_flat_members),
+                                    <
+                                        ::stecs::entity::EntityColumns<#field_flat_tys>
+                                        as ::stecs::entity::Columns
+                                    >::describe(len),
+                                ),
+                            )*
+                        ],
+                    },
+                }
+            }
+
+            fn into_iter(self) -> Self::IntoIter {
+                #ident_columns_into_iter {
+                    #(
+                        #field_column_idents: ::std::iter::IntoIterator::into_iter(
+                            self.#field_column_idents.into_vec(),
+                        ),
+                    )*
+                    #(#field_flat_idents: self.#field_flat_idents.into_iter(),)*
+                    #tags_phantom_init
                 }
             }
 
@@ -228,10 +692,11 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 &self,
                 len: usize,
             ) -> <Self::Entity as ::stecs::entity::Entity>::Fetch<#lifetime> {
-                #(::std::assert_eq!(len, self.#field_comp_idents.len());)*
+                #(::std::assert_eq!(len, self.#field_column_idents.len());)*
 
                 #ident_ref_fetch {
-                    #(#field_comp_idents: self.#field_comp_idents.as_raw_parts(),)*
+                    #(#field_column_idents: self.#field_column_idents.as_raw_parts(),)*
+                    #(#field_tag_idents: ::stecs::column::ColumnRawParts::tag(len),)*
                     #(#field_flat_idents: self.#field_flat_idents.new_fetch(len),)*
                     __stecs__len: len,
                     __stecs__phantom: ::std::marker::PhantomData,
@@ -242,10 +707,11 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 &self,
                 len: usize,
             ) -> <Self::Entity as ::stecs::entity::Entity>::FetchMut<#lifetime> {
-                #(::std::assert_eq!(len, self.#field_comp_idents.len());)*
+                #(::std::assert_eq!(len, self.#field_column_idents.len());)*
 
                 #ident_ref_mut_fetch {
-                    #(#field_comp_idents: self.#field_comp_idents.as_raw_parts_mut(),)*
+                    #(#field_column_idents: self.#field_column_idents.as_raw_parts_mut(),)*
+                    #(#field_tag_idents: ::stecs::column::ColumnRawPartsMut::tag(len),)*
                     #(#field_flat_idents: self.#field_flat_idents.new_fetch_mut(len),)*
                     __stecs__len: len,
                     __stecs__phantom: ::std::marker::PhantomData,
@@ -253,36 +719,86 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
             }
         }
 
+        impl #impl_generics ::stecs::snapshot::CloneFromChanged
+        for #ident_columns #ty_generics #where_clause
+        where
+            // https://github.com/rust-lang/rust/issues/48214#issuecomment-1150463333
+            #(for<'__stecs__a> #field_column_tys: ::std::clone::Clone,)*
+            #(
+                for<'__stecs__a> ::stecs::entity::EntityColumns<#field_flat_tys>:
+                    ::stecs::snapshot::CloneFromChanged,
+            )*
+        {
+            fn clone_from_changed(&mut self, source: &Self) {
+                #(
+                    ::stecs::snapshot::CloneFromChanged::clone_from_changed(
+                        &mut self.#field_column_idents,
+                        &source.#field_column_idents,
+                    );
+                )*
+                #(
+                    ::stecs::snapshot::CloneFromChanged::clone_from_changed(
+                        &mut self.#field_flat_idents,
+                        &source.#field_flat_idents,
+                    );
+                )*
+            }
+        }
+
+        // ColumnsIntoIter
+
+        #[allow(unused, non_camel_case_types)]
+        #vis struct #ident_columns_into_iter #impl_generics #where_clause {
+            #(#field_column_idents: ::std::vec::IntoIter<#field_column_tys>,)*
+            #(
+                #field_flat_idents: <
+                    ::stecs::entity::EntityColumns<#field_flat_tys>
+                    as ::stecs::entity::Columns
+                >::IntoIter,
+            )*
+            #tags_phantom_field
+        }
+
+        impl #impl_generics ::std::iter::Iterator
+        for #ident_columns_into_iter #ty_generics #where_clause {
+            type Item = #ident #ty_generics;
+
+            fn next(&mut self) -> ::std::option::Option<Self::Item> {
+                ::std::option::Option::Some(#ident {
+                    #(#field_column_members: self.#field_column_idents.next()?,)*
+                    #(#field_tag_members: #tag_defaults,)*
+                    #(#field_flat_members: self.#field_flat_idents.next()?,)*
+                })
+            }
+        }
+
         // Ref
 
         #[allow(unused, non_snake_case, non_camel_case_types)]
         #ref_derives
-        #vis struct #ident_ref #impl_generics_lifetime #where_clause_lifetime {
-            #(#vis #field_comp_idents: &#lifetime #field_comp_tys,)*
-            #(#vis #field_flat_idents: ::stecs::EntityRef<#lifetime, #field_flat_tys>,)*
-            __stecs__phantom: ::std::marker::PhantomData<&#lifetime ()>,
-        }
+        #vis struct #ident_ref #impl_generics_lifetime #ref_body
 
         impl #impl_generics_lifetime ::std::clone::Clone for #ident_ref #ty_generics_lifetime
         #where_clause_lifetime
         {
             fn clone(&self) -> Self {
                 Self {
-                    #(#field_comp_idents: self.#field_comp_idents,)*
-                    #(#field_flat_idents: self.#field_flat_idents.clone(),)*
-                    __stecs__phantom: ::std::marker::PhantomData,
+                    #(#field_comp_members: self.#field_comp_members,)*
+                    #(#field_flat_members: self.#field_flat_members.clone(),)*
+                    #phantom_member: ::std::marker::PhantomData,
                 }
             }
         }
 
+        #ref_debug
+
         // RefMut
 
         #[allow(unused, non_snake_case, non_camel_case_types)]
-        #vis struct #ident_ref_mut #impl_generics_lifetime #where_clause_lifetime {
-            #(#vis #field_comp_idents: &#lifetime mut #field_comp_tys,)*
-            #(#vis #field_flat_idents: ::stecs::EntityRefMut<#lifetime, #field_flat_tys>,)*
-            __stecs__phantom: ::std::marker::PhantomData<&#lifetime mut ()>,
-        }
+        #ref_mut_derives
+        #vis struct #ident_ref_mut #impl_generics_lifetime #ref_mut_body
+
+        #ref_mut_debug
 
         // RefFetch
 
@@ -312,7 +828,7 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
                 columns: &__stecs__T,
             ) -> ::std::option::Option<Self> {
-                ::stecs::entity::downcast_columns_ref(columns).map(|columns|
+                columns.flat_columns().map(|columns|
                     <#ident_columns #ty_generics as ::stecs::entity::Columns>
                     ::new_fetch(columns, ids.len())
                 )
@@ -329,9 +845,9 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 ::std::debug_assert!(index < self.len());
 
                 #ident_ref {
-                    #(#field_comp_idents: &*self.#field_comp_idents.ptr.add(index),)*
-                    #(#field_flat_idents: self.#field_flat_idents.get(index),)*
-                    __stecs__phantom: ::std::marker::PhantomData,
+                    #(#field_comp_members: &*self.#field_comp_idents.ptr.add(index),)*
+                    #(#field_flat_members: self.#field_flat_idents.get(index),)*
+                    #phantom_member: ::std::marker::PhantomData,
                 }
             }
         }
@@ -377,7 +893,7 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
                 columns: &__stecs__T,
             ) -> ::std::option::Option<Self> {
-                ::stecs::entity::downcast_columns_ref(columns).map(|columns|
+                columns.flat_columns().map(|columns|
                     <#ident_columns #ty_generics as ::stecs::entity::Columns>
                     ::new_fetch_mut(columns, ids.len())
                 )
@@ -394,9 +910,9 @@ pub fn derive(input: &DeriveInput, fields: &syn::FieldsNamed) -> Result<TokenStr
                 ::std::debug_assert!(index < self.len());
 
                 #ident_ref_mut {
-                    #(#field_comp_idents: &mut *self.#field_comp_idents.ptr.add(index),)*
-                    #(#field_flat_idents: self.#field_flat_idents.get(index),)*
-                    __stecs__phantom: ::std::marker::PhantomData,
+                    #(#field_comp_members: &mut *self.#field_comp_idents.get_ptr(index),)*
+                    #(#field_flat_members: self.#field_flat_idents.get(index),)*
+                    #phantom_member: ::std::marker::PhantomData,
                 }
             }
         }
@@ -16,7 +16,7 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
     .into()
 }
 
-#[proc_macro_derive(Query)]
+#[proc_macro_derive(Query, attributes(stecs))]
 pub fn derive_query(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match query::derive(input) {
@@ -2,7 +2,10 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{DeriveInput, Error, Result};
 
-use crate::utils::{members_as_idents, struct_fields};
+use crate::utils::{
+    generics_with_renamed_lifetime, get_query_attrs, members_as_idents, query_lifetime,
+    struct_fields, QueryAttrs,
+};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let ident = &input.ident;
@@ -20,49 +23,118 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let (field_tys, field_members) = struct_fields(&data.fields);
     let field_idents = members_as_idents(&field_members);
 
-    let lifetime = input
-        .generics
-        .lifetimes()
-        .next()
-        .map(|x| x.lifetime.clone());
-    let lifetime = match lifetime {
-        Some(x) => x,
-        None => {
-            return Err(Error::new_spanned(
-                input.generics,
-                "must have exactly one lifetime parameter",
-            ))
+    let QueryAttrs {
+        with,
+        without,
+        shared,
+    } = get_query_attrs(&input.attrs)?;
+
+    let lifetime = query_lifetime(&input.generics)?;
+    let lifetime_f: syn::Lifetime = syn::parse_str("'__stecs__f").unwrap();
+    let lifetime_w: syn::Lifetime = syn::parse_str("'__stecs__w").unwrap();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let generics_f = generics_with_renamed_lifetime(&input.generics, &lifetime, &lifetime_f);
+    let (_, ty_generics_f, _) = generics_f.split_for_impl();
+
+    let generics_w = generics_with_renamed_lifetime(&input.generics, &lifetime, &lifetime_w);
+    let (_, ty_generics_w, _) = generics_w.split_for_impl();
+
+    // With `#[stecs(shared = ...)]`, we generate a read-only twin of our
+    // struct, which derives `Query` and `QueryShared` in turn.
+    let shared = match shared {
+        Some(ident_shared) => {
+            let syn::Fields::Named(fields) = &data.fields else {
+                return Err(Error::new_spanned(
+                    ident,
+                    "#[stecs(shared = ...)] is only supported for structs with named fields",
+                ));
+            };
+            let field_viss = fields.named.iter().map(|field| &field.vis);
+
+            quote! {
+                #[derive(::stecs::Query, ::stecs::QueryShared)]
+                #(#[stecs(with = #with)])*
+                #(#[stecs(without = #without)])*
+                #vis struct #ident_shared #impl_generics #where_clause {
+                    #(
+                        #field_viss #field_idents:
+                            <#field_tys as ::stecs::query::IntoShared>::Shared,
+                    )*
+                }
+
+                impl #impl_generics ::stecs::query::IntoShared
+                for #ident #ty_generics #where_clause {
+                    type Shared = #ident_shared #ty_generics;
+
+                    fn into_shared(self) -> Self::Shared {
+                        #ident_shared {
+                            #(
+                                #field_idents: ::stecs::query::IntoShared::into_shared(
+                                    self.#field_idents,
+                                ),
+                            )*
+                        }
+                    }
+                }
+
+                impl #impl_generics ::std::convert::From<#ident #ty_generics>
+                for #ident_shared #ty_generics #where_clause {
+                    fn from(query: #ident #ty_generics) -> Self {
+                        ::stecs::query::IntoShared::into_shared(query)
+                    }
+                }
+            }
         }
+        None => quote! {},
     };
 
-    // TODO: We could probably remove this requirement.
-    if input.generics.params.len() != 1 {
-        return Err(Error::new_spanned(
-            ident,
-            "derive(Query) must have exactly one lifetime parameter and no type parameters",
-        ));
-    }
-
     Ok(quote! {
+        #shared
+
         // Fetch
 
+        // NOTE: We implement `Clone` and `Copy` by hand, since `derive` would
+        // require our type parameters to implement them as well.
         #[allow(unused, non_snake_case)]
-        #[derive(::std::clone::Clone, ::std::marker::Copy)]
-        #vis struct #ident_fetch<#lifetime> {
+        #vis struct #ident_fetch #impl_generics #where_clause {
             __stecs__len: usize,
             #(
                 #field_idents: <#field_tys as ::stecs::Query>::Fetch<#lifetime>,
             )*
         }
 
-        unsafe impl<#lifetime> ::stecs::query::fetch::Fetch
-        for #ident_fetch<#lifetime> {
-            type Item<'__stecs__f> = #ident<'__stecs__f> where Self: '__stecs__f;
+        impl #impl_generics ::std::clone::Clone for #ident_fetch #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::std::marker::Copy for #ident_fetch #ty_generics #where_clause {}
+
+        unsafe impl #impl_generics ::stecs::query::fetch::Fetch
+        for #ident_fetch #ty_generics #where_clause {
+            type Item<'__stecs__f> = #ident #ty_generics_f where Self: '__stecs__f;
 
             fn new<__stecs__T: ::stecs::entity::Columns>(
                 ids: &::stecs::column::Column<::stecs::thunderdome::Index>,
                 columns: &__stecs__T,
             ) -> ::std::option::Option<Self> {
+                #(
+                    <
+                        <#with as ::stecs::Query>::Fetch::<#lifetime>
+                        as ::stecs::query::fetch::Fetch
+                    >::new(ids, columns)?;
+                )*
+                #(
+                    if <
+                        <#without as ::stecs::Query>::Fetch::<#lifetime>
+                        as ::stecs::query::fetch::Fetch
+                    >::new(ids, columns).is_some() {
+                        return ::std::option::Option::None;
+                    }
+                )*
                 #(
                     let #field_idents = <
                         <#field_tys as ::stecs::Query>::Fetch::<#lifetime>
@@ -100,8 +172,8 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
 
         // Query
 
-        unsafe impl<#lifetime> ::stecs::Query for #ident<#lifetime> {
-            type Fetch<'__stecs__w> = #ident_fetch<'__stecs__w>;
+        unsafe impl #impl_generics ::stecs::Query for #ident #ty_generics #where_clause {
+            type Fetch<'__stecs__w> = #ident_fetch #ty_generics_w;
 
             fn for_each_borrow(mut f: impl FnMut(::std::any::TypeId, bool)) {
                 #(<#field_tys as ::stecs::Query>::for_each_borrow(&mut f);)*
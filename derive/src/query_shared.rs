@@ -2,7 +2,9 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{DeriveInput, Error, Result};
 
-use crate::utils::{members_as_idents, struct_fields};
+use crate::utils::{
+    generics_with_renamed_lifetime, members_as_idents, query_lifetime, struct_fields,
+};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let ident = &input.ident;
@@ -17,42 +19,37 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let (field_tys, field_members) = struct_fields(&data.fields);
     let field_idents = members_as_idents(&field_members);
 
-    let lifetime = input
-        .generics
-        .lifetimes()
-        .next()
-        .map(|x| x.lifetime.clone());
-    let lifetime = match lifetime {
-        Some(x) => x,
-        None => {
-            return Err(Error::new_spanned(
-                input.generics,
-                "must have exactly one lifetime parameter",
-            ))
-        }
-    };
+    let lifetime = query_lifetime(&input.generics)?;
+    let lifetime_q: syn::Lifetime = syn::parse_str("'__stecs__q").unwrap();
 
-    // TODO: We could probably remove this requirement.
-    if input.generics.params.len() != 1 {
-        return Err(Error::new_spanned(
-            ident,
-            "derive(QueryShared) must have exactly one lifetime parameter and no type parameters",
-        ));
-    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The field types below are bound by `for<#lifetime>`, so our own lifetime
+    // must have a different name.
+    let generics_q = generics_with_renamed_lifetime(&input.generics, &lifetime, &lifetime_q);
+    let (impl_generics_q, ty_generics_q, where_clause_q) = generics_q.split_for_impl();
+    let where_predicates: Vec<_> = where_clause_q
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .collect();
 
     Ok(quote! {
         // QueryShared
 
-        unsafe impl<'__stecs__q> ::stecs::QueryShared for #ident<'__stecs__q>
+        unsafe impl #impl_generics_q ::stecs::QueryShared for #ident #ty_generics_q
         where
+            #(#where_predicates,)*
             #(for<#lifetime> #field_tys: ::stecs::QueryShared,)*
         {
         }
 
-        const _: fn (#ident) = |query: #ident| {
-            fn check_field<Q: ::stecs::QueryShared>(_: Q) {}
+        const _: () = {
+            #[allow(dead_code)]
+            fn check #impl_generics (query: #ident #ty_generics) #where_clause {
+                fn check_field<Q: ::stecs::QueryShared>(_: Q) {}
 
-            #(check_field(query.#field_idents);)*
+                #(check_field(query.#field_idents);)*
+            }
         };
     })
 }
@@ -2,8 +2,8 @@ use std::borrow::Cow;
 
 use proc_macro2::Span;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{spanned::Spanned, Result};
+use quote::{quote, ToTokens};
+use syn::{spanned::Spanned, visit_mut::VisitMut, Result};
 
 pub fn associated_ident(ident: &syn::Ident, ty: &str) -> syn::Ident {
     syn::Ident::new(&format!("__stecs__{ident}{ty}"), ident.span())
@@ -29,18 +29,70 @@ pub fn parse_attr_names(attrs: &[syn::Attribute]) -> Result<Vec<String>> {
     Ok(names)
 }
 
-pub struct Derives {
-    pub id_derives: TokenStream2,
-    pub world_data_derives: TokenStream2,
-    pub columns_derives: TokenStream2,
-    pub ref_derives: TokenStream2,
+/// The traits that should be derived for one of the generated types.
+pub struct Derives(Vec<syn::Path>);
+
+impl Derives {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the trait `name` is derived, regardless of its path.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == name)
+        })
+    }
+
+    /// Removes the traits in `names`, regardless of their path, e.g. because
+    /// they are derived anyway.
+    pub fn without(self, names: &[&str]) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .filter(|path| {
+                    !path
+                        .segments
+                        .last()
+                        .is_some_and(|segment| names.iter().any(|name| segment.ident == name))
+                })
+                .collect(),
+        )
+    }
 }
 
-pub fn get_attr_derives(attrs: &[syn::Attribute]) -> Result<Derives> {
+impl ToTokens for Derives {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let paths = &self.0;
+
+        tokens.extend(quote! { #[derive(#(#paths,)*)] });
+    }
+}
+
+pub struct EntityAttrs {
+    pub id_derives: Derives,
+    pub world_data_derives: Derives,
+    pub columns_derives: Derives,
+    pub ref_derives: Derives,
+    pub ref_mut_derives: Derives,
+    pub diff_derives: Derives,
+    pub kind_derives: Derives,
+    pub kind: Option<syn::Ident>,
+    pub ordered: bool,
+}
+
+pub fn get_entity_attrs(attrs: &[syn::Attribute]) -> Result<EntityAttrs> {
+    let mut ordered = false;
     let mut id_paths = Vec::new();
     let mut world_data_paths = Vec::new();
     let mut columns_paths = Vec::new();
     let mut ref_paths = Vec::new();
+    let mut ref_mut_paths = Vec::new();
+    let mut diff_paths = Vec::new();
+    let mut kind_paths = Vec::new();
+    let mut kind = None;
 
     for attr in attrs {
         if !attr.path().is_ident("stecs") {
@@ -48,6 +100,16 @@ pub fn get_attr_derives(attrs: &[syn::Attribute]) -> Result<Derives> {
         }
 
         attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ordered") {
+                ordered = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("kind") {
+                kind = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+
             let paths = if meta.path.is_ident("derive_id") {
                 &mut id_paths
             } else if meta.path.is_ident("derive_world_data") {
@@ -56,6 +118,12 @@ pub fn get_attr_derives(attrs: &[syn::Attribute]) -> Result<Derives> {
                 &mut columns_paths
             } else if meta.path.is_ident("derive_ref") {
                 &mut ref_paths
+            } else if meta.path.is_ident("derive_ref_mut") {
+                &mut ref_mut_paths
+            } else if meta.path.is_ident("derive_diff") {
+                &mut diff_paths
+            } else if meta.path.is_ident("derive_kind") {
+                &mut kind_paths
             } else {
                 return Err(syn::Error::new(attr.span(), "Unknown attribute"));
             };
@@ -73,11 +141,16 @@ pub fn get_attr_derives(attrs: &[syn::Attribute]) -> Result<Derives> {
         })?;
     }
 
-    Ok(Derives {
-        id_derives: quote! { #[derive(#(#id_paths,)*)] },
-        world_data_derives: quote! { #[derive(#(#world_data_paths,)*)] },
-        columns_derives: quote! { #[derive(#(#columns_paths,)*)] },
-        ref_derives: quote! { #[derive(#(#ref_paths,)*)] },
+    Ok(EntityAttrs {
+        id_derives: Derives(id_paths),
+        world_data_derives: Derives(world_data_paths),
+        columns_derives: Derives(columns_paths),
+        ref_derives: Derives(ref_paths),
+        ref_mut_derives: Derives(ref_mut_paths),
+        diff_derives: Derives(diff_paths),
+        kind_derives: Derives(kind_paths),
+        kind,
+        ordered,
     })
 }
 
@@ -136,6 +209,88 @@ pub fn generics_with_new_lifetime(
     new_generics
 }
 
+/// Renames the lifetime parameter `from` to `to`, including all of its uses in
+/// bounds and where clauses.
+pub fn generics_with_renamed_lifetime(
+    generics: &syn::Generics,
+    from: &syn::Lifetime,
+    to: &syn::Lifetime,
+) -> syn::Generics {
+    struct RenameLifetime<'a> {
+        from: &'a syn::Lifetime,
+        to: &'a syn::Lifetime,
+    }
+
+    impl VisitMut for RenameLifetime<'_> {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+            if lifetime == self.from {
+                *lifetime = self.to.clone();
+            }
+        }
+    }
+
+    let mut new_generics = generics.clone();
+    RenameLifetime { from, to }.visit_generics_mut(&mut new_generics);
+
+    new_generics
+}
+
+/// Returns the single lifetime parameter of a query struct.
+pub fn query_lifetime(generics: &syn::Generics) -> Result<syn::Lifetime> {
+    let mut lifetimes = generics.lifetimes();
+
+    match (lifetimes.next(), lifetimes.next()) {
+        (Some(param), None) => Ok(param.lifetime.clone()),
+        _ => Err(syn::Error::new_spanned(
+            generics,
+            "must have exactly one lifetime parameter",
+        )),
+    }
+}
+
+pub struct QueryAttrs {
+    pub with: Vec<syn::Type>,
+    pub without: Vec<syn::Type>,
+    pub shared: Option<syn::Ident>,
+}
+
+pub fn get_query_attrs(attrs: &[syn::Attribute]) -> Result<QueryAttrs> {
+    let mut with = Vec::new();
+    let mut without = Vec::new();
+    let mut shared = None;
+
+    for attr in attrs {
+        if !attr.path().is_ident("stecs") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("shared") {
+                shared = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+
+            let tys = if meta.path.is_ident("with") {
+                &mut with
+            } else if meta.path.is_ident("without") {
+                &mut without
+            } else {
+                return Err(syn::Error::new(attr.span(), "Unknown attribute"));
+            };
+
+            tys.push(meta.value()?.parse()?);
+
+            Ok(())
+        })?;
+    }
+
+    Ok(QueryAttrs {
+        with,
+        without,
+        shared,
+    })
+}
+
 pub fn generics_with_new_type_param(
     generics: &syn::Generics,
     type_param: &syn::TypeParam,
@@ -81,9 +81,9 @@ pub struct SerDeThing {
 }
 */
 
-fn send_me<W: Send>(world: &W) {}
+fn send_me<W: Send>(_world: &W) {}
 
-fn sync_me<W: Sync>(world: &W) {}
+fn sync_me<W: Sync>(_world: &W) {}
 
 fn main() {
     let mut world = World::default();
@@ -413,7 +413,7 @@ fn main() {
         p.0 += q.0;
     }
 
-    for (enemy, pos) in world.query_mut::<(EntityRefMut<Enemy>, &Position)>() {
+    for (enemy, _pos) in world.query_mut::<(EntityRefMut<Enemy>, &Position)>() {
         dbg!(enemy.target.0, enemy.pos.0);
 
         *enemy.pos = Position(enemy.pos.0 + 100.0);
@@ -0,0 +1,165 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use stecs::{Id, World};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Sprite {
+    scale: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Text {
+    size: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+trait Drawable {
+    fn draw(&self) -> String;
+
+    fn grow(&mut self);
+}
+
+impl Drawable for Sprite {
+    fn draw(&self) -> String {
+        format!("sprite x{}", self.scale)
+    }
+
+    fn grow(&mut self) {
+        self.scale *= 2;
+    }
+}
+
+impl Drawable for Text {
+    fn draw(&self) -> String {
+        format!("text {}pt", self.size)
+    }
+
+    fn grow(&mut self) {
+        self.size += 1;
+    }
+}
+
+stecs::dyn_trait!(Drawable: Sprite, Text);
+
+#[derive(stecs::Entity, Clone)]
+struct Player {
+    pos: Position,
+    sprite: Sprite,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Label {
+    pos: Position,
+    text: Text,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Sign {
+    sprite: Sprite,
+    text: Text,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Wall {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone)]
+enum Entity {
+    Player(Player),
+    Label(Label),
+    Sign(Sign),
+    Wall(Wall),
+}
+
+fn drawings(world: &World<Entity>) -> Vec<(Id<Entity>, String)> {
+    world
+        .query::<(Id<Entity>, &dyn Drawable)>()
+        .into_iter()
+        .map(|(id, drawable)| (id, drawable.draw()))
+        .collect()
+}
+
+fn expect_panic(f: impl FnOnce()) {
+    assert!(panic::catch_unwind(AssertUnwindSafe(f)).is_err());
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let player = world.spawn(Player {
+        pos: Position(0),
+        sprite: Sprite { scale: 1 },
+    });
+    let label = world.spawn(Label {
+        pos: Position(1),
+        text: Text { size: 12 },
+    });
+    let sign = world.spawn(Sign {
+        sprite: Sprite { scale: 3 },
+        text: Text { size: 8 },
+    });
+    world.spawn(Wall { pos: Position(2) });
+
+    // Every archetype with a drawable component matches, and the sign is drawn
+    // as its sprite, since `Sprite` is listed first.
+    let expected = [
+        (player.to_outer(), "sprite x1".to_owned()),
+        (label.to_outer(), "text 12pt".to_owned()),
+        (sign.to_outer(), "sprite x3".to_owned()),
+    ];
+    assert_eq!(drawings(&world), expected);
+
+    for drawable in world.query_mut::<&mut dyn Drawable>() {
+        drawable.grow();
+    }
+    let expected = [
+        (player.to_outer(), "sprite x2".to_owned()),
+        (label.to_outer(), "text 13pt".to_owned()),
+        (sign.to_outer(), "sprite x6".to_owned()),
+    ];
+    assert_eq!(drawings(&world), expected);
+
+    // The sign's text is not visible through the trait object.
+    assert_eq!(world.get::<&Text>(sign.to_outer()), Some(&Text { size: 8 }));
+
+    for (pos, drawable) in world.query_mut::<(&mut Position, &mut dyn Drawable)>() {
+        pos.0 += 10;
+        drawable.grow();
+    }
+    assert_eq!(
+        world.get::<&Position>(label.to_outer()),
+        Some(&Position(11))
+    );
+    assert_eq!(
+        world.get::<&Text>(label.to_outer()),
+        Some(&Text { size: 14 })
+    );
+    assert_eq!(
+        world.get::<&Sprite>(sign.to_outer()),
+        Some(&Sprite { scale: 6 })
+    );
+
+    // The queries borrow all registered components, even in archetypes where
+    // they are not used, so they conflict with other borrows of any of them.
+    panic::set_hook(Box::new(|_| {}));
+    expect_panic(|| {
+        world.query_mut::<(&mut dyn Drawable, &Text)>();
+    });
+    expect_panic(|| {
+        world.query_mut::<(&dyn Drawable, &mut Sprite)>();
+    });
+    let _ = panic::take_hook();
+
+    let texts: Vec<_> = world
+        .query::<(&dyn Drawable, &Text)>()
+        .into_iter()
+        .map(|(drawable, text)| (drawable.draw(), text.size))
+        .collect();
+    assert_eq!(
+        texts,
+        [("text 14pt".to_owned(), 14), ("sprite x6".to_owned(), 8)]
+    );
+}
@@ -0,0 +1,99 @@
+use std::{
+    any::TypeId,
+    panic::{self, AssertUnwindSafe},
+};
+
+use stecs::{dynamic::DynQuery, Id, World};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Speed(i32);
+
+#[derive(stecs::Entity, Clone)]
+struct Car {
+    pos: Position,
+    speed: Speed,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Tree {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone)]
+enum Entity {
+    Car(Car),
+    Tree(Tree),
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let car = world.spawn(Car {
+        pos: Position(0),
+        speed: Speed(2),
+    });
+    let tree = world.spawn(Tree { pos: Position(5) });
+
+    // Dynamic queries visit the same entities as static ones, in the same
+    // order.
+    let positions = DynQuery::new().read(TypeId::of::<Position>());
+    let ids: Vec<_> = world.dyn_query(&positions).map(|(id, _)| id).collect();
+    let expected: Vec<_> = world.query::<Id<Entity>>().into_iter().collect();
+    assert_eq!(ids, expected);
+    assert_eq!(ids, [car.to_outer(), tree.to_outer()]);
+
+    let (id, row) = world.dyn_query(&positions).nth(1).unwrap();
+    assert_eq!(id, tree.to_outer());
+    assert_eq!(row.len(), 1);
+    assert_eq!(row.get(0).unwrap().downcast_ref(), Some(&Position(5)));
+    assert!(row.get(1).is_none());
+
+    // Only archetypes that have all components are visited.
+    let moving = DynQuery::new()
+        .write(TypeId::of::<Position>())
+        .read(TypeId::of::<Speed>());
+
+    for (_, mut row) in world.dyn_query_mut(&moving) {
+        let speed = *row.get(1).unwrap().downcast_ref::<Speed>().unwrap();
+        assert!(row.get_mut(1).is_none());
+
+        let pos = row.get_mut(0).unwrap().downcast_mut::<Position>().unwrap();
+        pos.0 += speed.0;
+    }
+    assert_eq!(world.get::<&Position>(car.to_outer()), Some(&Position(2)));
+    assert_eq!(world.get::<&Position>(tree.to_outer()), Some(&Position(5)));
+
+    for (_, row) in world.dyn_query_mut(&moving) {
+        let mut components = row.into_components();
+        let mut pos = components.next().unwrap();
+        let speed = components.next().unwrap();
+        assert!(components.next().is_none());
+
+        let speed = speed.as_any().downcast_ref::<Speed>().unwrap().0;
+        pos.as_any_mut()
+            .unwrap()
+            .downcast_mut::<Position>()
+            .unwrap()
+            .0 -= speed;
+    }
+    assert_eq!(world.get::<&Position>(car.to_outer()), Some(&Position(0)));
+
+    // Queries that violate the borrowing rules panic before visiting anything.
+    panic::set_hook(Box::new(|_| {}));
+    let aliasing = DynQuery::new()
+        .write(TypeId::of::<Position>())
+        .read(TypeId::of::<Position>());
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        world.dyn_query_mut(&aliasing).count();
+    }));
+    assert!(result.is_err());
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        world.dyn_query(&moving).count();
+    }));
+    assert!(result.is_err());
+    let _ = panic::take_hook();
+}
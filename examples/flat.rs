@@ -1,4 +1,4 @@
-use stecs::{CloneEntityFromRef, Id};
+use stecs::{CloneEntityFromRef, EntityRefMut, Id};
 
 #[derive(stecs::Entity, Clone)]
 #[stecs(derive_columns(Clone))]
@@ -14,10 +14,17 @@ struct Bullet {
     projectile: Projectile,
 }
 
+// Tuple structs and unit structs can be entities too.
+#[derive(stecs::Entity, Clone)]
+struct Spark(#[stecs(flat)] Projectile, u8);
+
 #[derive(stecs::Entity, Clone)]
 enum Entity {
     Projectile(Projectile),
     Bullet(Bullet),
+    Spark(Spark),
+    // Variants without a payload are entities without components.
+    Marker,
 }
 
 type World = stecs::World<Entity>;
@@ -25,10 +32,12 @@ type World = stecs::World<Entity>;
 fn main() {
     let mut world = World::default();
 
-    world.spawn(Projectile {
-        position: 1.0,
-        velocity: -3,
-    });
+    let projectile = world
+        .spawn(Projectile {
+            position: 1.0,
+            velocity: -3,
+        })
+        .to_outer();
 
     let id = world.spawn(Bullet {
         projectile: Projectile {
@@ -37,9 +46,41 @@ fn main() {
         },
     });
 
-    for id in world.query::<Id<Entity>>().with::<(&f32, &i32)>() {
-        dbg!(id);
+    let spark = world
+        .spawn(Spark(
+            Projectile {
+                position: 3.0,
+                velocity: 5,
+            },
+            255,
+        ))
+        .to_outer();
+
+    let marker = world.spawn(Entity::Marker);
+
+    let mut ids: Vec<_> = world
+        .query::<Id<Entity>>()
+        .with::<(&f32, &i32)>()
+        .into_iter()
+        .collect();
+    ids.sort_by_key(|id| id.to_string());
+    assert_eq!(ids, [id.to_outer(), projectile, spark]);
+
+    // Queries for `Projectile` also match the entities that embed one, so
+    // this moves the `Bullet` and the `Spark` as well, but not the `Marker`.
+    let mut moved = Vec::new();
+    for (id, projectile) in world.query_mut::<(Id<Entity>, EntityRefMut<Projectile>)>() {
+        *projectile.position += *projectile.velocity as f32;
+        moved.push(id);
     }
+    moved.sort_by_key(|id| id.to_string());
+    assert_eq!(moved, [id.to_outer(), projectile, spark]);
+    assert!(!moved.contains(&marker));
+
+    assert_eq!(world.get::<&f32>(projectile), Some(&-2.0));
+    assert_eq!(world.get::<&f32>(id.to_outer()), Some(&-2.0));
+    assert_eq!(world.get::<&f32>(spark), Some(&8.0));
+    assert_eq!(world.get::<&u8>(spark), Some(&255));
 
     let entity_ref = world.entity(id).unwrap();
     let _ = Bullet::clone_entity_from_ref(entity_ref);
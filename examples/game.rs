@@ -1,4 +1,4 @@
-use stecs::{EntityRef, EntityRefMut, Id};
+use stecs::{EntityRef, EntityRefMut, Id, Kind};
 
 // Components
 
@@ -169,18 +169,9 @@ fn update_bullets(world: &mut World) {
 }
 
 fn despawn_dead(world: &mut World) {
-    let dead: Vec<_> = world
-        .query::<(Id<Entity>, &Health)>()
-        .into_iter()
-        .filter(|(_, health)| health.0 <= 0)
-        .map(|(id, _)| id)
-        .collect();
-
-    for id in dead {
-        let Some(entity) = world.despawn(id) else {
-            continue;
-        };
-
+    // `drain_filter` compacts the storage in a single pass, instead of looking
+    // up and removing each dead entity individually.
+    for (_, entity) in world.drain_filter::<&Health>(|health| health.0 <= 0) {
         match entity {
             Entity::Player(entity) => println!("Killed Player: {:?}", entity.pos),
             Entity::Enemy(entity) => println!("Killed Enemy: {:?}", entity.pos),
@@ -200,8 +191,6 @@ fn run_tick(world: &mut World) {
 fn print_world(world: &World) {
     for entity in world.query::<EntityRef<Entity>>() {
         // We can pattern match entity references to do type-specific things.
-        // TODO: Allow specifying traits that should be derived for the
-        // generated `EntityRef` etc. types.
         match entity {
             EntityRef::<Entity>::Player(entity) => {
                 println!("Good Player: {:?} {:?}", entity.pos, entity.health)
@@ -209,9 +198,20 @@ fn print_world(world: &World) {
             EntityRef::<Entity>::Enemy(entity) => {
                 println!("Evil Enemy: {:?} {:?}", entity.pos, entity.health)
             }
-            EntityRef::<Entity>::Bullet(_) => {}
+            // Entity references implement `Debug` if all components do. Other
+            // traits can be derived with `#[stecs(derive_ref(...))]`.
+            EntityRef::<Entity>::Bullet(entity) => println!("Bullet: {entity:?}"),
         }
     }
+
+    // When only the variant matters, we can query for the generated
+    // `EntityKind` instead, which is read from the ids.
+    let mut counts = std::collections::BTreeMap::new();
+    for kind in world.query::<Kind<Entity>>() {
+        *counts.entry(kind).or_insert(0) += 1;
+    }
+
+    println!("Entities by kind: {counts:?}");
 }
 
 fn main() {
@@ -0,0 +1,107 @@
+use stecs::{Component, Id, World};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+// The same game logic, instantiated with different network state on the
+// client and the server.
+#[derive(Clone, Debug, PartialEq)]
+struct Predicted(i32);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Authority {
+    owner: u8,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Player<Net: Component> {
+    pos: Position,
+    net: Net,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Bullet {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Projectile<Net: Component> {
+    Bullet(Bullet),
+    Rocket(Player<Net>),
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Entity<Net: Component + Clone> {
+    Player(Player<Net>),
+    Projectile(Projectile<Net>),
+}
+
+fn step<Net: Component + Clone>(world: &mut World<Entity<Net>>) {
+    for pos in world.query_mut::<&mut Position>() {
+        pos.0 += 1;
+    }
+}
+
+fn main() {
+    let mut client = World::<Entity<Predicted>>::new();
+    let mut server = World::<Entity<Authority>>::new();
+
+    let predicted = client.spawn(Player {
+        pos: Position(0),
+        net: Predicted(1),
+    });
+    let bullet = client.spawn(Projectile::Bullet(Bullet { pos: Position(10) }));
+    let authority = server.spawn(Player {
+        pos: Position(0),
+        net: Authority { owner: 2 },
+    });
+
+    step(&mut client);
+    step(&mut server);
+
+    assert_eq!(
+        client.get::<&Position>(predicted.to_outer()),
+        Some(&Position(1))
+    );
+    assert_eq!(
+        client.get::<&Position>(bullet.to_outer()),
+        Some(&Position(11))
+    );
+    assert_eq!(
+        client.query::<&Predicted>().into_iter().collect::<Vec<_>>(),
+        [&Predicted(1)]
+    );
+    assert_eq!(
+        server
+            .query::<&Authority>()
+            .into_iter()
+            .map(|net| net.owner)
+            .collect::<Vec<_>>(),
+        [2]
+    );
+
+    // Ids of generic enums round trip through their bits and strings.
+    for id in [predicted.to_outer(), bullet.to_outer()] {
+        assert_eq!(Id::<Entity<Predicted>>::from_bits(id.to_bits()), Some(id));
+        assert_eq!(id.to_string().parse::<Id<Entity<Predicted>>>(), Ok(id));
+    }
+    assert_eq!(bullet.to_string(), "Bullet#0v1");
+    assert_eq!(authority.to_string(), "Player#0v1");
+
+    // Generic variants can be nested, and despawned through any level.
+    let rocket = client.spawn(Projectile::Rocket(Player {
+        pos: Position(20),
+        net: Predicted(3),
+    }));
+    assert_eq!(client.len(), 3);
+    assert_eq!(
+        client.despawn(rocket),
+        Some(Entity::Projectile(Projectile::Rocket(Player {
+            pos: Position(20),
+            net: Predicted(3),
+        })))
+    );
+    assert!(client.despawn(predicted).is_some());
+    assert_eq!(client.len(), 1);
+    client.validate().unwrap();
+}
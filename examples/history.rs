@@ -0,0 +1,78 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use stecs::history::History;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Node {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Entity {
+    Node(Node),
+}
+
+fn positions(history: &History<Entity>) -> Vec<i32> {
+    let mut positions: Vec<_> = history
+        .world()
+        .query::<&Position>()
+        .into_iter()
+        .map(|pos| pos.0)
+        .collect();
+    positions.sort();
+
+    positions
+}
+
+fn main() {
+    let mut history = History::<Entity>::default();
+
+    let a = history.spawn(Node { pos: Position(1) });
+    let b = history.spawn(Node { pos: Position(2) });
+    history.entity_mut(a).unwrap().pos.0 = 10;
+    assert_eq!(positions(&history), [2, 10]);
+
+    assert!(history.undo());
+    assert_eq!(positions(&history), [1, 2]);
+    assert!(history.redo());
+    assert_eq!(positions(&history), [2, 10]);
+
+    // Looking at an entity without changing it does not create an edit.
+    let _ = history.entity_mut(b).unwrap().pos.0;
+    history.despawn(b);
+    assert!(history.undo());
+    assert!(history.world().contains(b));
+    assert!(history.undo());
+    assert_eq!(positions(&history), [1, 2]);
+
+    // A group is undone as a whole.
+    history.group(|history| {
+        history.despawn(a);
+        history.entity_mut(b).unwrap().pos.0 = 20;
+        history.spawn(Node { pos: Position(3) });
+    });
+    assert_eq!(positions(&history), [3, 20]);
+    assert!(history.undo());
+    assert_eq!(positions(&history), [1, 2]);
+
+    // The edits made before a panic in a group still form a transaction, and
+    // later edits are not merged into it.
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        history.group(|history| {
+            history.despawn(a);
+            panic!("failed halfway");
+        })
+    }));
+    let _ = panic::take_hook();
+    assert!(result.is_err());
+
+    history.spawn(Node { pos: Position(4) });
+    assert!(history.undo());
+    assert_eq!(positions(&history), [2]);
+    assert!(history.undo());
+    assert_eq!(positions(&history), [1, 2]);
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::HashSet,
+    panic::{self, AssertUnwindSafe},
+};
+
+use stecs::{Id, Kind, World};
+
+#[derive(stecs::Entity, Clone)]
+struct Player {
+    name: &'static str,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Goblin {
+    health: u32,
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Troll {
+    health: u32,
+}
+
+#[derive(stecs::Entity, Clone)]
+enum Enemy {
+    Goblin(Goblin),
+    Brute(Troll),
+}
+
+#[derive(stecs::Entity, Clone)]
+struct Loot<T: Clone + 'static> {
+    item: T,
+}
+
+// The kind always derives the standard traits, so deriving them again is
+// allowed and has no effect.
+#[derive(stecs::Entity, Clone)]
+#[stecs(derive_kind(Debug, std::hash::Hash))]
+enum Entity {
+    Player(Player),
+    Enemy(Enemy),
+    Loot(Loot<(u8, u8)>),
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let player = world.spawn(Player { name: "hero" }).to_outer::<Entity>();
+    let goblin = world.spawn(Enemy::Goblin(Goblin { health: 3 }));
+    let troll = world.spawn(Enemy::Brute(Troll { health: 8 }));
+    let loot = world.spawn(Loot { item: (1, 2) });
+
+    // Ids survive a round trip through their bits, at each level of nesting.
+    for id in [player, goblin.to_outer(), troll.to_outer()] {
+        assert_eq!(Id::<Entity>::from_bits(id.to_bits()), Some(id));
+        assert_eq!(world.validate_id(id.to_bits()), Some(id));
+    }
+    assert_eq!(Id::<Enemy>::from_bits(goblin.to_bits()), Some(goblin));
+
+    let inner = goblin.try_to_inner::<Goblin>().unwrap();
+    assert_eq!(Id::<Goblin>::from_bits(inner.to_bits()), Some(inner));
+
+    // The variant is part of the bits, so a goblin and a troll in the same
+    // slot have different bits.
+    let troll_inner = troll.try_to_inner::<Troll>().unwrap();
+    assert_eq!(inner.to_bits(), troll_inner.to_bits());
+    assert_ne!(goblin.to_bits(), troll.to_bits());
+    assert_eq!(goblin.try_to_inner::<Troll>(), None);
+
+    // Ids of despawned entities are rejected, even if their slot is reused.
+    let bits = goblin.to_outer::<Entity>().to_bits();
+    world.despawn(goblin);
+    assert_eq!(world.validate_id(bits), None);
+
+    let new_goblin = world.spawn(Enemy::Goblin(Goblin { health: 2 }));
+    assert_eq!(world.validate_id(bits), None);
+    assert_eq!(
+        world.validate_id(new_goblin.to_outer::<Entity>().to_bits()),
+        Some(new_goblin.to_outer())
+    );
+
+    // Slots of any variant have to leave room for all variant bits of the
+    // outermost enum, not only for its discriminant.
+    let player_inner = player.try_to_inner::<Player>().unwrap();
+    let large = Id::<Player>::from_bits(player_inner.to_bits() | 1 << 31).unwrap();
+    assert_eq!(Id::<Player>::from_bits(large.to_bits()), Some(large));
+    assert_eq!(Id::<Entity>::from_bits(player.to_bits() | 1 << 31), None);
+
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        large.to_outer::<Entity>().to_bits();
+    }));
+    assert!(result.is_err());
+    let _ = panic::take_hook();
+
+    // Ids are displayed with the name of their innermost variant, and parsed
+    // back from it.
+    assert_eq!(player.to_string(), "Player#0v1");
+    assert_eq!(troll.to_string(), "Brute#0v1");
+    assert_eq!(troll.to_outer::<Entity>().to_string(), "Brute#0v1");
+    assert_eq!(loot.to_outer::<Entity>().to_string(), "Loot#0v1");
+    assert_eq!(new_goblin.to_string(), "Goblin#0v2");
+
+    for id in [player, troll.to_outer(), loot.to_outer()] {
+        assert_eq!(id.to_string().parse::<Id<Entity>>(), Ok(id));
+    }
+    assert_eq!("Brute#0v1".parse::<Id<Enemy>>(), Ok(troll));
+    assert!("Brute#0v1".parse::<Id<Troll>>().is_err());
+    assert!("Dragon#0v1".parse::<Id<Entity>>().is_err());
+
+    // Struct ids use the struct name, without generic arguments.
+    assert_eq!(loot.to_string(), "Loot#0v1");
+    assert_eq!("Loot#0v1".parse::<Id<Loot<(u8, u8)>>>(), Ok(loot));
+    assert_eq!(
+        troll.try_to_inner::<Troll>().unwrap().to_string(),
+        "Troll#0v1"
+    );
+
+    // Kinds are read from the ids.
+    assert_eq!(player.kind(), EntityKind::Player);
+    assert_eq!(troll.to_outer::<Entity>().kind(), EntityKind::Enemy);
+    assert_eq!(troll.kind(), EnemyKind::Brute);
+
+    let kinds: HashSet<_> = world.query::<Kind<Entity>>().into_iter().collect();
+    assert_eq!(kinds, HashSet::from(EntityKind::ALL));
+    assert_eq!(
+        EntityKind::ALL.map(EntityKind::name),
+        ["Player", "Enemy", "Loot"]
+    );
+}
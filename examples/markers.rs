@@ -0,0 +1,106 @@
+use stecs::{layout::EntityLayoutKind, Id, World};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Value(u32);
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Checkpoint;
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Coin(Position, Value);
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Spawner {}
+
+// Payloadless variants do not clash with items of the same name.
+#[derive(Debug)]
+struct Trigger {
+    radius: u32,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Entity {
+    Checkpoint(Checkpoint),
+    Coin(Coin),
+    Spawner(Spawner),
+    Trigger,
+    Goal,
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let checkpoint = world.spawn(Checkpoint).to_outer();
+    let coin = world.spawn(Coin(Position(1), Value(5)));
+    let spawner = world.spawn(Spawner {}).to_outer();
+    let trigger = world.spawn(Entity::Trigger);
+    let goal = world.spawn(Entity::Goal);
+    assert_eq!(world.len(), 5);
+
+    // Entities without components match queries that do not need any.
+    let mut ids: Vec<_> = world.query::<Id<Entity>>().into_iter().collect();
+    ids.sort_by_key(|id| id.to_string());
+    let mut expected = [checkpoint, coin.to_outer(), spawner, trigger, goal];
+    expected.sort_by_key(|id| id.to_string());
+    assert_eq!(ids, expected);
+
+    let coins: Vec<_> = world
+        .query::<(Id<Coin>, &Position, &Value)>()
+        .into_iter()
+        .collect();
+    assert_eq!(coins, [(coin, &Position(1), &Value(5))]);
+
+    // Payloadless variants show up under their own name.
+    assert_eq!(format!("{:?}", world.entity(trigger).unwrap()), "Trigger");
+    assert_eq!(format!("{:?}", world.entity(goal).unwrap()), "Goal");
+    assert_eq!(trigger.to_string(), "Trigger#0v1");
+    assert_eq!(checkpoint.to_string(), "Checkpoint#0v1");
+
+    for id in expected {
+        assert_eq!(id.to_string().parse::<Id<Entity>>(), Ok(id));
+        assert_eq!(Id::<Entity>::from_bits(id.to_bits()), Some(id));
+    }
+
+    let EntityLayoutKind::Enum { variants } = world.describe().kind else {
+        unreachable!();
+    };
+    for (variant, layout) in variants {
+        assert_eq!(variant, layout.name);
+        assert_eq!(layout.len, 1);
+    }
+    for archetype in world.memory_stats().archetypes {
+        assert!(!archetype.entity.contains("__stecs__"));
+    }
+
+    // Despawning gives back the variant.
+    assert_eq!(world.despawn(trigger), Some(Entity::Trigger));
+    assert_eq!(world.despawn(trigger), None);
+    assert_eq!(
+        world.despawn(coin),
+        Some(Entity::Coin(Coin(Position(1), Value(5))))
+    );
+
+    let trigger = world.spawn(Entity::Trigger);
+    assert_eq!(trigger.to_string(), "Trigger#0v2");
+
+    let mut entities: Vec<_> = world
+        .into_iter()
+        .map(|(id, entity)| (id.to_string(), entity))
+        .collect();
+    entities.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        entities,
+        [
+            ("Checkpoint#0v1".to_owned(), Entity::Checkpoint(Checkpoint)),
+            ("Goal#0v1".to_owned(), Entity::Goal),
+            ("Spawner#0v1".to_owned(), Entity::Spawner(Spawner {})),
+            ("Trigger#0v2".to_owned(), Entity::Trigger),
+        ]
+    );
+
+    let trigger = Trigger { radius: 2 };
+    assert_eq!(trigger.radius, 2);
+}
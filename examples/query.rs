@@ -1,4 +1,4 @@
-use stecs::Or;
+use stecs::{Component, Or, World};
 
 #[derive(Clone)]
 pub struct Position(f32);
@@ -6,7 +6,16 @@ pub struct Position(f32);
 #[derive(Clone)]
 pub struct Velocity(f32);
 
+#[derive(Clone)]
+pub struct Frozen;
+
+#[derive(Clone)]
+pub struct Name(&'static str);
+
+// `PhysicsObjectRef<'a>` is generated as a read-only twin, with each `&'a mut`
+// turned into `&'a`.
 #[derive(stecs::Query)]
+#[stecs(shared = PhysicsObjectRef)]
 pub struct PhysicsObjectMut<'a> {
     position: &'a mut Position,
     velocity: &'a mut Velocity,
@@ -19,4 +28,146 @@ pub struct PhysicsObject<'a> {
     x: Or<&'a (), &'a ()>,
 }
 
-fn main() {}
+// Query structs can be generic, nest other query structs, and declare filters
+// that restrict them to archetypes with or without some component.
+#[derive(stecs::Query)]
+#[stecs(without = &'a Frozen)]
+pub struct MovingObjectMut<'a, T: Component> {
+    object: PhysicsObjectMut<'a>,
+    tag: Option<&'a T>,
+}
+
+// Bounds and where clauses may refer to the query lifetime.
+#[derive(stecs::Query, stecs::QueryShared)]
+pub struct Tagged<'a, T>
+where
+    T: Component + Clone + 'a,
+{
+    position: &'a Position,
+    tag: &'a T,
+}
+
+pub trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for Position {
+    fn describe(&self) -> String {
+        format!("at {}", self.0)
+    }
+}
+
+impl Describe for Velocity {
+    fn describe(&self) -> String {
+        format!("moving at {}", self.0)
+    }
+}
+
+// `&dyn Describe` queries match every entity that has a `Position` or a
+// `Velocity`, preferring the `Position` if it has both.
+stecs::dyn_trait!(Describe: Position, Velocity);
+
+#[derive(stecs::Query, stecs::QueryShared)]
+pub struct Described<'a> {
+    object: &'a dyn Describe,
+    frozen: Option<&'a Frozen>,
+}
+
+#[derive(stecs::Entity, Clone)]
+pub struct Ball {
+    pos: Position,
+    vel: Velocity,
+    name: Name,
+    unit: (),
+}
+
+#[derive(stecs::Entity, Clone)]
+pub struct Block {
+    pos: Position,
+    vel: Velocity,
+    frozen: Frozen,
+    unit: (),
+}
+
+#[derive(stecs::Entity, Clone)]
+pub struct Wall {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone)]
+pub enum Entity {
+    Ball(Ball),
+    Block(Block),
+    Wall(Wall),
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    world.spawn(Ball {
+        pos: Position(0.0),
+        vel: Velocity(1.0),
+        name: Name("ball"),
+        unit: (),
+    });
+    world.spawn(Block {
+        pos: Position(10.0),
+        vel: Velocity(1.0),
+        frozen: Frozen,
+        unit: (),
+    });
+    world.spawn(Wall { pos: Position(5.0) });
+
+    for object in world.query_mut::<PhysicsObjectMut>() {
+        object.velocity.0 *= 2.0;
+    }
+
+    // The frozen block is skipped.
+    for moving in world.query_mut::<MovingObjectMut<Name>>() {
+        assert_eq!(moving.tag.map(|name| name.0), Some("ball"));
+
+        moving.object.position.0 += moving.object.velocity.0;
+    }
+
+    // Both sides of the `Or` refer to the same component, so both match.
+    let mut objects: Vec<_> = world
+        .query::<PhysicsObject>()
+        .into_iter()
+        .inspect(|object| assert!(matches!(object.x, Or::Both(&(), &()))))
+        .map(|object| (object.position.0, object.velocity.0))
+        .collect();
+    objects.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(objects, [(2.0, 2.0), (10.0, 2.0)]);
+
+    // The read-only twin matches the same entities.
+    let twins = world
+        .query::<PhysicsObjectRef>()
+        .into_iter()
+        .map(|object| (object.position.0, object.velocity.0));
+    assert!(twins.eq(world
+        .query::<PhysicsObject>()
+        .into_iter()
+        .map(|object| (object.position.0, object.velocity.0))));
+
+    let tagged: Vec<_> = world
+        .query::<Tagged<Name>>()
+        .into_iter()
+        .map(|tagged| (tagged.tag.clone().0, tagged.position.0))
+        .collect();
+    assert_eq!(tagged, [("ball", 2.0)]);
+
+    let mut descriptions: Vec<_> = world
+        .query::<Described>()
+        .into_iter()
+        .map(|described| (described.object.describe(), described.frozen.is_some()))
+        .collect();
+    descriptions.sort();
+    assert_eq!(
+        descriptions,
+        [
+            ("at 10".to_owned(), true),
+            ("at 2".to_owned(), false),
+            ("at 5".to_owned(), false),
+        ]
+    );
+}
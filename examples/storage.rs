@@ -0,0 +1,88 @@
+use stecs::World;
+
+// Despawning shifts the following messages, which keeps them in spawn order.
+#[derive(stecs::Entity, Clone)]
+#[stecs(ordered)]
+struct Message {
+    text: &'static str,
+}
+
+#[derive(stecs::Entity, Clone)]
+enum Entity {
+    Message(Message),
+}
+
+fn texts(world: &World<Entity>) -> Vec<&'static str> {
+    world
+        .variant::<Message>()
+        .iter()
+        .map(|(_, message)| *message.text)
+        .collect()
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let ids: Vec<_> = world
+        .spawn_batch(["a", "b", "c"].map(|text| Message { text }))
+        .collect();
+
+    // The new message reuses the slot of the despawned one.
+    world.despawn(ids[0]);
+    let id = world.spawn(Message { text: "d" });
+    assert_eq!(texts(&world), ["b", "c", "d"]);
+
+    // Defragmenting keeps ordered archetypes in spawn order.
+    world.defragment();
+    assert_eq!(texts(&world), ["b", "c", "d"]);
+    assert_eq!(world.entity(id).map(|message| *message.text), Some("d"));
+
+    // Vacant slots and reserved space both count as spare capacity, so it
+    // only bounds the number of vacant slots from above.
+    let stats = &world.memory_stats().archetypes[0];
+    assert_eq!(stats.len, 3);
+    assert_eq!(stats.spare_capacity, stats.slots - stats.len);
+
+    let mut world = World::<Entity>::new();
+    world.reserve::<Message>(16);
+    let id = world.spawn(Message { text: "a" });
+    world.spawn(Message { text: "b" });
+    world.despawn(id);
+
+    let stats = &world.memory_stats().archetypes[0];
+    assert_eq!(stats.len, 1);
+    assert!(stats.slots >= 16);
+    assert_eq!(stats.spare_capacity, stats.slots - 1);
+    // Only one slot is vacant, but the reserved slots count as well.
+    assert!(stats.spare_capacity > 1);
+
+    // Reserving grows the columns, but only preallocates arena slots while the
+    // arena has none.
+    let mut world = World::<Entity>::new();
+    world.reserve::<Message>(8);
+    let stats = &world.memory_stats().archetypes[0];
+    assert!(stats.slots >= 8);
+    assert!(stats.ids.capacity >= 8);
+    assert!(stats.columns.iter().all(|(_, column)| column.capacity >= 8));
+
+    let _ = world.spawn_batch(["a", "b"].map(|text| Message { text }));
+    let slots = world.memory_stats().archetypes[0].slots;
+    world.reserve::<Message>(100);
+    let stats = &world.memory_stats().archetypes[0];
+    assert_eq!(stats.slots, slots);
+    assert!(stats.ids.capacity >= 102);
+    assert!(stats
+        .columns
+        .iter()
+        .all(|(_, column)| column.capacity >= 102));
+
+    world.shrink_to_fit();
+    let stats = &world.memory_stats().archetypes[0];
+    assert_eq!(stats.slots, slots);
+    assert!(stats.ids.capacity < 100);
+    assert!(stats
+        .columns
+        .iter()
+        .all(|(_, column)| column.capacity < 100));
+    assert_eq!(texts(&world), ["a", "b"]);
+}
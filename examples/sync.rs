@@ -0,0 +1,111 @@
+use stecs::{checksum::Divergence, Id, World};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Position(i32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Health(u32);
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+#[stecs(derive_ref(Hash))]
+struct Player {
+    pos: Position,
+    health: Health,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+#[stecs(derive_ref(Hash))]
+struct Coin {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+#[stecs(derive_ref(Hash))]
+enum Entity {
+    Player(Player),
+    Coin(Coin),
+}
+
+fn spawn_level(world: &mut World<Entity>) {
+    world.spawn(Player {
+        pos: Position(0),
+        health: Health(3),
+    });
+
+    for x in 1..4 {
+        world.spawn(Coin { pos: Position(x) });
+    }
+}
+
+fn main() {
+    let (mut ours, mut theirs) = (World::<Entity>::new(), World::<Entity>::new());
+    spawn_level(&mut ours);
+    spawn_level(&mut theirs);
+
+    // The checksum does not depend on the storage order.
+    theirs.sort_variant_by_key::<Coin, _>(|coin| std::cmp::Reverse(coin.pos.0));
+    assert_eq!(ours.checksum(), theirs.checksum());
+    assert_eq!(ours.checksums().total(), ours.checksum());
+
+    // Diverge by moving the player in one world and despawning a coin in the
+    // other.
+    let (player, _) = ours
+        .query::<(Id<Entity>, &Health)>()
+        .into_iter()
+        .next()
+        .unwrap();
+    *ours.get_mut::<&mut Position>(player).unwrap() = Position(1);
+
+    let coin = ours.query::<Id<Coin>>().into_iter().next().unwrap();
+    theirs.despawn(coin);
+
+    assert_ne!(ours.checksum(), theirs.checksum());
+
+    let divergences: Vec<_> = ours.checksums().divergences(&theirs.checksums()).collect();
+    assert_eq!(divergences.len(), 2);
+    assert!(divergences.contains(&Divergence::Changed(player)));
+    assert!(divergences.contains(&Divergence::OnlyInSelf(coin.to_outer())));
+
+    // Bring `theirs` up to date by applying the difference.
+    let diff = World::diff(&theirs, &ours);
+    assert_eq!(diff.spawned.len(), 1);
+    assert_eq!(diff.spawned[0].0, coin.to_outer());
+    assert!(diff.despawned.is_empty());
+    assert_eq!(diff.changed.len(), 1);
+
+    theirs.apply_diff(diff);
+    assert_eq!(ours.checksum(), theirs.checksum());
+    assert!(World::diff(&theirs, &ours).is_empty());
+
+    // Roll back to a snapshot after a coin has been collected and its slot has
+    // been reused.
+    let snapshot = ours.snapshot();
+    let checksum = ours.checksum();
+
+    ours.despawn(coin);
+    let new_coin = ours.spawn(Coin { pos: Position(5) });
+    assert_eq!(
+        (coin.to_string(), new_coin.to_string()),
+        ("Coin#0v1".into(), "Coin#0v2".into())
+    );
+
+    for pos in ours.query_mut::<&mut Position>() {
+        pos.0 += 10;
+    }
+
+    ours.restore(&snapshot);
+    assert_eq!(ours.checksum(), checksum);
+    assert!(ours.contains(coin));
+    assert!(!ours.contains(new_coin));
+    assert!(World::diff(&ours, &theirs).is_empty());
+
+    // Reusing the snapshot only copies what changed, and keeps it in sync.
+    let mut snapshot = snapshot;
+    ours.get_mut::<&mut Health>(player).unwrap().0 = 1;
+    ours.snapshot_into(&mut snapshot);
+    ours.restore(&theirs.snapshot());
+    assert_eq!(ours.checksum(), checksum);
+
+    ours.restore(&snapshot);
+    assert_eq!(ours.get::<&Health>(player), Some(&Health(1)));
+}
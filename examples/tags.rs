@@ -0,0 +1,118 @@
+use stecs::{Id, World};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(i32);
+
+// Tags take no storage, and are recreated with `Default` when an entity is
+// moved out of the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Frozen;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Enemy;
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Block {
+    pos: Position,
+    #[stecs(tag)]
+    frozen: Frozen,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Goblin {
+    pos: Position,
+    #[stecs(tag)]
+    enemy: Enemy,
+    #[stecs(tag)]
+    frozen: Frozen,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Ball {
+    pos: Position,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Entity {
+    Block(Block),
+    Goblin(Goblin),
+    Ball(Ball),
+}
+
+fn sorted<T: Ord>(mut items: Vec<T>) -> Vec<T> {
+    items.sort();
+    items
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let block = world.spawn(Block {
+        pos: Position(1),
+        frozen: Frozen,
+    });
+    let goblin = world.spawn(Goblin {
+        pos: Position(2),
+        enemy: Enemy,
+        frozen: Frozen,
+    });
+    let ball = world.spawn(Ball { pos: Position(3) });
+
+    // Tags have no column.
+    let layout = world.describe();
+    assert!(layout.components().all(|component| component.size > 0));
+
+    // Tags can be queried by reference, like any other component.
+    let frozen: Vec<_> = world
+        .query::<(&Position, &Frozen)>()
+        .into_iter()
+        .map(|(pos, frozen)| (pos.0, *frozen))
+        .collect();
+    assert_eq!(sorted(frozen), [(1, Frozen), (2, Frozen)]);
+
+    let enemies: Vec<_> = world.query::<(Id<Entity>, &Enemy)>().into_iter().collect();
+    assert_eq!(enemies, [(goblin.to_outer(), &Enemy)]);
+
+    // Filters on tags are resolved per archetype.
+    let thawed: Vec<_> = world
+        .query::<Id<Entity>>()
+        .without::<&Frozen>()
+        .into_iter()
+        .collect();
+    assert_eq!(thawed, [ball.to_outer()]);
+
+    for pos in world
+        .query_mut::<&mut Position>()
+        .with::<&Frozen>()
+        .without::<&Enemy>()
+    {
+        pos.0 = 0;
+    }
+    assert_eq!(world.get::<&Position>(block.to_outer()), Some(&Position(0)));
+    assert_eq!(
+        world.get::<&Position>(goblin.to_outer()),
+        Some(&Position(2))
+    );
+
+    // Tagged entities can be moved out of the world.
+    assert_eq!(
+        world.despawn(goblin),
+        Some(Entity::Goblin(Goblin {
+            pos: Position(2),
+            enemy: Enemy,
+            frozen: Frozen,
+        }))
+    );
+
+    let entities: Vec<_> = world.into_iter().map(|(_, entity)| entity).collect();
+    assert_eq!(
+        entities,
+        [
+            Entity::Block(Block {
+                pos: Position(0),
+                frozen: Frozen,
+            }),
+            Entity::Ball(Ball { pos: Position(3) }),
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use stecs::World;
+
+#[derive(stecs::Entity, Clone)]
+struct Particle {
+    value: u32,
+}
+
+#[derive(stecs::Entity, Clone)]
+#[stecs(ordered)]
+struct Event {
+    value: u32,
+}
+
+#[derive(stecs::Entity, Clone)]
+enum Entity {
+    Particle(Particle),
+    Event(Event),
+}
+
+/// A small deterministic random number generator, so that the example does
+/// not need any dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, n: u32) -> u32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+
+        ((self.0 >> 33) % u64::from(n)) as u32
+    }
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+    let mut expected = HashMap::new();
+    let mut rng = Rng(1);
+
+    for value in 0..2000 {
+        match rng.next(8) {
+            0 | 1 => {
+                let id = world.spawn(Particle { value }).to_outer();
+                expected.insert(id, value);
+            }
+            2 => {
+                let id = world.spawn(Event { value }).to_outer();
+                expected.insert(id, value);
+            }
+            3 => {
+                let ids = world.spawn_batch((0..rng.next(4)).map(|i| Event { value: value + i }));
+                for (i, id) in ids.enumerate() {
+                    expected.insert(id.to_outer(), value + i as u32);
+                }
+            }
+            4 | 5 => {
+                let ids: Vec<_> = expected.keys().copied().collect();
+                if !ids.is_empty() {
+                    let id = ids[rng.next(ids.len() as u32) as usize];
+                    assert!(world.despawn(id).is_some());
+                    assert!(world.despawn(id).is_none());
+                    expected.remove(&id);
+                }
+            }
+            6 => {
+                let modulus = rng.next(5) + 2;
+                world.retain::<&u32>(|value| value % modulus != 0);
+                expected.retain(|_, value| *value % modulus != 0);
+            }
+            _ => {
+                world.defragment();
+            }
+        }
+
+        world.validate().unwrap();
+        assert_eq!(world.len(), expected.len());
+    }
+
+    for (&id, &expected) in &expected {
+        assert_eq!(world.get::<&u32>(id), Some(&expected));
+    }
+
+    // Ids of despawned entities stay invalid, even after their slots have
+    // been reused many times.
+    let id = world.spawn(Particle { value: 0 });
+    world.despawn(id);
+    let _ = world.spawn_batch((0..100).map(|value| Particle { value }));
+    assert!(!world.contains(id));
+    world.validate().unwrap();
+}
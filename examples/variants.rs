@@ -0,0 +1,131 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use stecs::World;
+
+// Mutable entity references derive `Debug` on their own, other traits have to
+// be asked for.
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+#[stecs(derive_ref_mut(PartialEq))]
+struct Player {
+    score: u32,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+struct Coin {
+    value: u32,
+}
+
+#[derive(stecs::Entity, Clone, Debug, PartialEq)]
+enum Entity {
+    Player(Player),
+    Coin(Coin),
+}
+
+fn coins(world: &World<Entity>) -> Vec<u32> {
+    world
+        .variant::<Coin>()
+        .iter()
+        .map(|(_, coin)| *coin.value)
+        .collect()
+}
+
+fn expect_panic(f: impl FnOnce()) {
+    assert!(panic::catch_unwind(AssertUnwindSafe(f)).is_err());
+}
+
+fn main() {
+    let mut world = World::<Entity>::new();
+
+    let player = world.spawn(Player { score: 0 });
+    let _ = world.spawn_batch((1..=4).map(|value| Coin { value }));
+
+    // Per-variant storages can be modified directly.
+    for (_, coin) in world.variant_mut::<Coin>().iter_mut() {
+        *coin.value *= 10;
+    }
+    world
+        .variant_mut::<Coin>()
+        .retain(|_, coin| *coin.value != 20);
+    assert_eq!(coins(&world), [10, 30, 40]);
+    assert_eq!(world.len(), 4);
+
+    // Disjoint variants can be borrowed mutably at the same time.
+    let (players, coins_data) = world.split_variants_mut::<(Player, Coin)>();
+    for (_, coin) in coins_data.iter() {
+        for (_, player) in players.iter_mut() {
+            *player.score += *coin.value;
+        }
+    }
+    assert_eq!(world.get::<&u32>(player.to_outer()), Some(&80));
+
+    let mut other = World::<Entity>::new();
+    assert_eq!(other.spawn(Player { score: 80 }), player);
+    assert!(world.entity_mut(player) == other.entity_mut(player));
+    *other.entity_mut(player).unwrap().score += 1;
+    assert!(world.entity_mut(player) != other.entity_mut(player));
+    assert_eq!(
+        format!("{:?}", world.entity_mut(player).unwrap()),
+        format!("{:?}", world.entity(player).unwrap())
+    );
+
+    // Overlapping splits panic.
+    panic::set_hook(Box::new(|_| {}));
+    expect_panic(|| {
+        world.split_variants_mut::<(Coin, Coin)>();
+    });
+    expect_panic(|| {
+        world.split_variants_mut::<(Entity, Player)>();
+    });
+    expect_panic(|| {
+        world.split_variants_mut::<(Entity,)>();
+    });
+    let _ = panic::take_hook();
+    world.validate().unwrap();
+
+    // Draining yields the entities in storage order.
+    let ids: Vec<_> = world.variant::<Coin>().iter().map(|(id, _)| id).collect();
+    let drained: Vec<_> = world.variant_mut::<Coin>().drain().collect();
+    assert_eq!(
+        drained,
+        [
+            (ids[0], Coin { value: 10 }),
+            (ids[1], Coin { value: 30 }),
+            (ids[2], Coin { value: 40 }),
+        ]
+    );
+    assert_eq!(world.len(), 1);
+    assert!(!world.contains(ids[0]));
+
+    // A leaked drain leaves the storage empty, not corrupt.
+    let _ = world.spawn_batch((1..=3).map(|value| Coin { value }));
+    let mut drain = world.variant_mut::<Coin>().drain();
+    assert_eq!(drain.next().map(|(_, coin)| coin.value), Some(1));
+    std::mem::forget(drain);
+
+    world.validate().unwrap();
+    assert_eq!(world.len(), 1);
+    assert!(coins(&world).is_empty());
+    assert_eq!(world.query::<&u32>().into_iter().count(), 1);
+
+    // Worlds can be collected from and extended with entities of any variant.
+    let mut world: World<Entity> = (1..=2).map(|value| Coin { value }).collect();
+    world.extend([Player { score: 7 }]);
+    world.extend([Entity::Coin(Coin { value: 3 })]);
+    assert_eq!(world.len(), 4);
+    assert_eq!(coins(&world), [1, 2, 3]);
+    let scores: Vec<_> = world
+        .variant::<Player>()
+        .iter()
+        .map(|(_, player)| *player.score)
+        .collect();
+    assert_eq!(scores, [7]);
+    world.validate().unwrap();
+
+    // So can archetypes.
+    let mut archetype: <Coin as stecs::Entity>::WorldData =
+        (1..=2).map(|value| Coin { value }).collect();
+    archetype.extend([Coin { value: 5 }]);
+    let values: Vec<_> = archetype.iter().map(|(_, coin)| *coin.value).collect();
+    assert_eq!(values, [1, 2, 5]);
+    assert_eq!(archetype.len(), 3);
+}
@@ -1,8 +1,11 @@
 use std::{
-    any::type_name,
-    fmt::{self, Debug},
+    any::{type_name, TypeId},
+    fmt::{self, Debug, Display},
     marker::PhantomData,
     option,
+    rc::Rc,
+    str::FromStr,
+    vec,
 };
 
 use derivative::Derivative;
@@ -10,10 +13,15 @@ use thunderdome::Arena;
 
 use crate::{
     column::Column,
-    entity::{Columns, EntityVariant},
+    dynamic::{AnyColumn, DynArchetype},
+    entity::{Columns, EntityVariant, IdBits, NamedId, ParseIdError},
+    layout::EntityLayout,
     query::fetch::Fetch,
+    snapshot::CloneFromChanged,
+    stats::{ArchetypeStats, MemoryStats},
+    validate::ValidationError,
     world::WorldFetch,
-    EntityRef, Id, WorldData,
+    EntityRef, EntityRefMut, Id, WorldData,
 };
 
 #[derive(Derivative)]
@@ -63,6 +71,62 @@ impl<E> EntityKey<E> {
     }
 }
 
+impl<E> IdBits for EntityKey<E> {
+    const VARIANT_BITS: u32 = 0;
+
+    fn to_bits(self) -> u64 {
+        self.0.to_bits()
+    }
+
+    fn from_bits(bits: u64) -> Option<Self> {
+        thunderdome::Index::from_bits(bits).map(Self::new_unchecked)
+    }
+}
+
+/// Returns the name of `E` without module paths and generic arguments, e.g.
+/// `Boier` for `game::Boier<u32, f32>`.
+fn short_type_name<E>() -> &'static str {
+    let name = type_name::<E>();
+    let name = name.split('<').next().unwrap_or(name);
+
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl<E> NamedId for EntityKey<E> {
+    fn fmt_named(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}v{}", name, self.0.slot(), self.0.generation())
+    }
+
+    fn parse_named(s: &str, name: &str) -> Option<Self> {
+        let (id_name, index) = s.rsplit_once('#')?;
+        let (slot, generation) = index.split_once('v')?;
+
+        if id_name != name {
+            return None;
+        }
+
+        let slot: u32 = slot.parse().ok()?;
+        let generation: u32 = generation.parse().ok()?;
+
+        thunderdome::Index::from_bits((u64::from(generation) << 32) | u64::from(slot))
+            .map(Self::new_unchecked)
+    }
+}
+
+impl<E> Display for EntityKey<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_named(short_type_name::<E>(), f)
+    }
+}
+
+impl<E> FromStr for EntityKey<E> {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_named(s, short_type_name::<E>()).ok_or_else(|| ParseIdError::new(s))
+    }
+}
+
 impl<E> Debug for EntityKey<E> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple(&format!("EntityKey::<{}>", type_name::<E>()))
@@ -71,6 +135,12 @@ impl<E> Debug for EntityKey<E> {
     }
 }
 
+/// Storage for all entities of one struct type.
+///
+/// By default, despawning an entity moves the last entity into its place, so
+/// iteration order depends on the despawn history. Entity structs annotated
+/// with `#[stecs(ordered)]` instead shift the following entities, which keeps
+/// them in spawn order at the cost of `O(n)` despawns.
 #[derive(Clone)]
 pub struct Archetype<T: Columns> {
     indices: Arena<usize>,
@@ -85,6 +155,7 @@ impl<T: Columns> Archetype<T> {
 
         self.ids.push(id.0);
         self.columns.push(entity);
+        self.debug_validate_at(index);
 
         Id::new(id)
     }
@@ -94,10 +165,37 @@ impl<T: Columns> Archetype<T> {
 
         self.ids.push(id.get().0);
         self.columns.push(entity);
+        self.debug_validate_at(self.ids.len() - 1);
     }
 
     fn despawn_impl(&mut self, id: Id<T::Entity>) -> Option<T::Entity> {
+        let index = *self.indices.get(id.get().0)?;
+        let entity = self.despawn_unchecked_impl(id);
+
+        // Ordered despawns move all following entities, so they are checked
+        // in full. Otherwise, only the entity moved into `index` changed.
+        if T::ORDERED {
+            self.debug_validate();
+        } else {
+            self.debug_validate_at(index);
+        }
+
+        entity
+    }
+
+    fn despawn_unchecked_impl(&mut self, id: Id<T::Entity>) -> Option<T::Entity> {
         let index = self.indices.remove(id.get().0)?;
+
+        if T::ORDERED {
+            self.ids.shift_remove(index);
+
+            for (index, &id) in self.ids.as_slice().iter().enumerate().skip(index) {
+                self.indices[id] = index;
+            }
+
+            return Some(self.columns.shift_remove(index));
+        }
+
         let is_last = index + 1 == self.ids.len();
 
         self.ids.remove(index);
@@ -109,7 +207,179 @@ impl<T: Columns> Archetype<T> {
         Some(self.columns.remove(index))
     }
 
-    pub fn get_impl(&mut self, id: Id<T::Entity>) -> Option<EntityRef<T::Entity>> {
+    /// Moves the entities for which `f` returns `false` to the end of the
+    /// storage, keeping `indices` up to date. Returns the number of entities
+    /// that were kept.
+    ///
+    /// `f` is called exactly once for each entity, in storage order, with the
+    /// entity's current index. The kept entities stay in their relative order.
+    /// The columns are not reallocated.
+    fn partition_impl(&mut self, mut f: impl FnMut(thunderdome::Index, usize) -> bool) -> usize {
+        let mut kept = 0;
+
+        for index in 0..self.ids.len() {
+            let id = *self.ids.get(index);
+
+            if !f(id, index) {
+                continue;
+            }
+
+            if index != kept {
+                let other_id = *self.ids.get(kept);
+
+                self.ids.swap(index, kept);
+                self.columns.swap(index, kept);
+
+                self.indices[id] = kept;
+                self.indices[other_id] = index;
+            }
+
+            kept += 1;
+        }
+
+        kept
+    }
+
+    fn truncate_impl(&mut self, len: usize) {
+        for index in len..self.ids.len() {
+            self.indices.remove(*self.ids.get(index));
+        }
+
+        self.ids.truncate(len);
+        self.columns.truncate(len);
+        self.debug_validate();
+    }
+
+    /// Reorders the entities so that the entity at index `order[i]` moves to
+    /// index `i`, keeping `indices` up to date.
+    fn permute_impl(&mut self, order: &[usize]) {
+        debug_assert_eq!(order.len(), self.ids.len());
+
+        // `positions[j]` is the current index of the entity that was at index
+        // `j` originally, and `entities[i]` is the inverse of that.
+        let mut positions: Vec<usize> = (0..order.len()).collect();
+        let mut entities = positions.clone();
+
+        for (index, &original) in order.iter().enumerate() {
+            let current = positions[original];
+
+            if current != index {
+                self.ids.swap(index, current);
+                self.columns.swap(index, current);
+
+                let displaced = entities[index];
+                positions[displaced] = current;
+                entities[current] = displaced;
+            }
+
+            positions[original] = index;
+            entities[index] = original;
+        }
+
+        for (index, &id) in self.ids.as_slice().iter().enumerate() {
+            self.indices[id] = index;
+        }
+
+        self.debug_validate();
+    }
+
+    fn validate_impl(&self) -> Result<(), ValidationError> {
+        self.validate_lens()?;
+
+        // Every id leads back to its own index, and since there are as many
+        // occupied slots as ids, this is a bijection.
+        (0..self.ids.len()).try_for_each(|index| self.validate_index(index))
+    }
+
+    fn validate_lens(&self) -> Result<(), ValidationError> {
+        let name = T::describe(0).name;
+        let len = self.ids.len();
+
+        if self.indices.len() != len {
+            return Err(ValidationError::new(format!(
+                "{name}: {} ids, but {} occupied slots",
+                len,
+                self.indices.len(),
+            )));
+        }
+
+        let mut result = Ok(());
+
+        self.columns.for_each_column(&mut |field, column| {
+            if result.is_ok() && column.len() != len {
+                result = Err(ValidationError::new(format!(
+                    "{name}: {len} ids, but column `{field}` has length {}",
+                    column.len(),
+                )));
+            }
+        });
+
+        result
+    }
+
+    /// Checks that the id at `index` is occupied, with the same generation,
+    /// and that its slot points back to `index`. Indices past the end are
+    /// ignored.
+    fn validate_index(&self, index: usize) -> Result<(), ValidationError> {
+        let Some(&id) = self.ids.as_slice().get(index) else {
+            return Ok(());
+        };
+
+        match self.indices.get(id) {
+            Some(&slot_index) if slot_index == index => Ok(()),
+            Some(&slot_index) => Err(ValidationError::new(format!(
+                "{}: index {index} holds {id:?}, but its slot points to index {slot_index}",
+                T::describe(0).name,
+            ))),
+            None => Err(ValidationError::new(format!(
+                "{}: index {index} holds {id:?}, whose slot is vacant or has another generation",
+                T::describe(0).name,
+            ))),
+        }
+    }
+
+    /// Panics if an invariant is broken and the `debug-checks` feature is
+    /// enabled.
+    fn debug_validate(&self) {
+        #[cfg(feature = "debug-checks")]
+        if let Err(err) = self.validate_impl() {
+            panic!("{err}");
+        }
+    }
+
+    /// Like `debug_validate`, but only checks the entity at `index`, so that
+    /// single spawns and despawns stay `O(1)`.
+    #[cfg_attr(not(feature = "debug-checks"), allow(unused_variables))]
+    fn debug_validate_at(&self, index: usize) {
+        #[cfg(feature = "debug-checks")]
+        if let Err(err) = self
+            .validate_lens()
+            .and_then(|()| self.validate_index(index))
+        {
+            panic!("{err}");
+        }
+    }
+
+    fn reserve_impl(&mut self, additional: usize) {
+        // `thunderdome::Arena` has no `reserve`, so we can only preallocate it
+        // before its first allocation. Replacing an arena that has been used
+        // would reset the slot generations and thereby revive stale `Id`s.
+        if self.indices.capacity() == 0 {
+            self.indices = Arena::with_capacity(additional);
+        }
+
+        self.ids.reserve(additional);
+        self.columns.reserve(additional);
+    }
+
+    fn shrink_to_fit_impl(&mut self) {
+        // `thunderdome::Arena` has no `shrink_to_fit`, so `indices` keeps its
+        // capacity.
+        self.ids.shrink_to_fit();
+        self.columns.shrink_to_fit();
+    }
+
+    pub fn get_impl(&mut self, id: Id<T::Entity>) -> Option<EntityRef<'_, T::Entity>> {
         let index = *self.indices.get(id.get().0)?;
 
         debug_assert!(index < self.ids.len());
@@ -131,7 +401,230 @@ impl<T: Columns> Default for Archetype<T> {
     }
 }
 
-// TODO: impl<T> IntoIterator for Archetype<T>
+impl<T: Columns> Archetype<T> {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Despawns all entities.
+    ///
+    /// `Id`s of the despawned entities remain invalid, since the slot
+    /// generations are kept.
+    pub fn clear(&mut self) {
+        self.truncate_impl(0);
+    }
+
+    /// Iterates over all entities in storage order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Id<T::Entity>, EntityRef<'_, T::Entity>)> {
+        let fetch = self.columns.new_fetch(self.ids.len());
+
+        self.ids
+            .as_slice()
+            .iter()
+            .enumerate()
+            .map(move |(index, &id)| {
+                // Safety: We only hand out shared references.
+                let entity = unsafe { fetch.get(index) };
+
+                (Id::new(EntityKey::new_unchecked(id)), entity)
+            })
+    }
+
+    /// Iterates mutably over all entities in storage order.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl ExactSizeIterator<Item = (Id<T::Entity>, EntityRefMut<'_, T::Entity>)> {
+        let fetch = self.columns.new_fetch_mut(self.ids.len());
+
+        self.ids
+            .as_slice()
+            .iter()
+            .enumerate()
+            .map(move |(index, &id)| {
+                // Safety: Each index is visited only once, and we hold an
+                // exclusive borrow of `self`.
+                let entity = unsafe { fetch.get(index) };
+
+                (Id::new(EntityKey::new_unchecked(id)), entity)
+            })
+    }
+
+    /// Sorts the entities by the key returned by `f`. The sort is stable, and
+    /// `Id`s remain valid.
+    pub fn sort_by_key<K: Ord>(&mut self, mut f: impl FnMut(EntityRef<'_, T::Entity>) -> K) {
+        let keys: Vec<_> = self.iter().map(|(_, entity)| f(entity)).collect();
+
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+
+        self.permute_impl(&order);
+    }
+
+    /// Sorts the entities by their `Id`'s slot and shrinks the storage to fit.
+    ///
+    /// Afterwards, iteration order matches the order in which the `indices`
+    /// slots are laid out. `Id`s remain valid. The free slots of `indices`
+    /// are not compacted, since that would reset their generations and
+    /// thereby revive stale `Id`s.
+    ///
+    /// Archetypes declared with `#[stecs(ordered)]` are only shrunk, since
+    /// slots are reused after despawns and sorting by them would break the
+    /// spawn order.
+    pub fn defragment(&mut self) {
+        if T::ORDERED {
+            self.shrink_to_fit_impl();
+            return;
+        }
+
+        let ids = self.ids.as_slice();
+
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_unstable_by_key(|&index| ids[index].slot());
+
+        self.permute_impl(&order);
+        self.shrink_to_fit_impl();
+    }
+
+    /// Despawns all entities, returning them in storage order.
+    ///
+    /// The archetype is emptied right away, so it stays consistent even if
+    /// the iterator is leaked. If the iterator is dropped early, the remaining
+    /// entities are dropped as well. Unlike `clear`, this releases the
+    /// storage.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.indices.clear();
+
+        let ids = std::mem::take(&mut self.ids);
+        let columns = std::mem::take(&mut self.columns);
+        self.debug_validate();
+
+        Drain(
+            IntoIter {
+                ids: ids.into_vec().into_iter(),
+                columns: columns.into_iter(),
+            },
+            PhantomData,
+        )
+    }
+
+    /// Despawns all entities for which `f` returns `false`.
+    ///
+    /// The storage is compacted in a single pass. The kept entities stay in
+    /// their previous order.
+    pub fn retain(
+        &mut self,
+        mut f: impl FnMut(Id<T::Entity>, EntityRefMut<'_, T::Entity>) -> bool,
+    ) {
+        let fetch = self.columns.new_fetch_mut(self.ids.len());
+
+        let kept = self.partition_impl(|id, index| {
+            // Safety: `partition_impl` visits each entity once without
+            // reallocating, and the reference does not outlive the call.
+            let entity = unsafe { fetch.get(index) };
+
+            f(Id::new(EntityKey::new_unchecked(id)), entity)
+        });
+
+        self.truncate_impl(kept);
+    }
+}
+
+/// Iterator returned by [`Archetype::drain`].
+pub struct Drain<'a, T: Columns>(IntoIter<T>, PhantomData<&'a mut Archetype<T>>);
+
+impl<'a, T: Columns> Iterator for Drain<'a, T> {
+    type Item = (Id<T::Entity>, T::Entity);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T: Columns> ExactSizeIterator for Drain<'a, T> {}
+
+impl<T: Columns> IntoIterator for Archetype<T> {
+    type Item = (Id<T::Entity>, T::Entity);
+
+    type IntoIter = IntoIter<T>;
+
+    /// Moves all entities out of the archetype, in storage order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            ids: self.ids.into_vec().into_iter(),
+            columns: self.columns.into_iter(),
+        }
+    }
+}
+
+/// Iterator returned by [`Archetype::into_iter`].
+pub struct IntoIter<T: Columns> {
+    ids: vec::IntoIter<thunderdome::Index>,
+    columns: T::IntoIter,
+}
+
+impl<T: Columns> Iterator for IntoIter<T> {
+    type Item = (Id<T::Entity>, T::Entity);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `ids` determines the length, since `columns` does not end for
+        // entities without components.
+        let id = self.ids.next()?;
+        let entity = self
+            .columns
+            .next()
+            .expect("Columns should have the same length as ids");
+
+        Some((Id::new(EntityKey::new_unchecked(id)), entity))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl<T: Columns> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Columns> Extend<T::Entity> for Archetype<T> {
+    fn extend<I: IntoIterator<Item = T::Entity>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_impl(iter.size_hint().0);
+
+        for entity in iter {
+            self.spawn_impl(entity);
+        }
+    }
+}
+
+impl<T: Columns> FromIterator<T::Entity> for Archetype<T> {
+    fn from_iter<I: IntoIterator<Item = T::Entity>>(iter: I) -> Self {
+        let mut archetype = Self::default();
+        archetype.extend(iter);
+
+        archetype
+    }
+}
+
+impl<T> CloneFromChanged for Archetype<T>
+where
+    T: Columns + CloneFromChanged,
+{
+    fn clone_from_changed(&mut self, source: &Self) {
+        // `indices` can only change together with `ids`.
+        if self.ids.clone_from_changed(&source.ids) {
+            self.indices.clone_from(&source.indices);
+        }
+
+        self.columns.clone_from_changed(&source.columns);
+    }
+}
 
 pub struct ArchetypeWorldFetch<'w, F, T>(&'w Arena<usize>, Option<F>, PhantomData<T>);
 
@@ -197,7 +690,7 @@ impl<T: Columns> WorldData for Archetype<T> {
     }
 
     fn spawn_at(&mut self, id: Id<Self::Entity>, entity: Self::Entity) -> Option<Self::Entity> {
-        let old = self.despawn(id);
+        let old = self.despawn_unchecked_impl(id);
 
         self.spawn_at_impl(id, entity);
 
@@ -208,6 +701,109 @@ impl<T: Columns> WorldData for Archetype<T> {
         self.indices.contains(id.get().0)
     }
 
+    fn len(&self) -> usize {
+        Archetype::len(self)
+    }
+
+    fn clear(&mut self) {
+        Archetype::clear(self)
+    }
+
+    fn reserve<E>(&mut self, additional: usize)
+    where
+        E: EntityVariant<Self::Entity>,
+    {
+        self.reserve_impl(additional);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit_impl();
+    }
+
+    fn defragment(&mut self) {
+        Archetype::defragment(self)
+    }
+
+    fn describe(&self) -> EntityLayout {
+        T::describe(self.len())
+    }
+
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_impl()
+    }
+
+    fn memory_stats(&self) -> MemoryStats {
+        let mut columns = Vec::new();
+
+        self.columns.for_each_column(&mut |field, column| {
+            columns.push((field.to_string(), column.stats()));
+        });
+
+        let stats = ArchetypeStats {
+            variant: Vec::new(),
+            entity: T::describe(0).name,
+            len: self.len(),
+            slots: self.indices.capacity(),
+            spare_capacity: self.indices.capacity() - self.indices.len(),
+            ids: self.ids.stats(),
+            columns,
+        };
+
+        MemoryStats {
+            archetypes: vec![stats],
+        }
+    }
+
+    fn dyn_column(&self, id: Id<Self::Entity>, type_id: TypeId) -> Option<(&dyn AnyColumn, usize)> {
+        let index = *self.indices.get(id.get().0)?;
+        let column = self.columns.dyn_column(type_id)?;
+
+        Some((column, index))
+    }
+
+    fn dyn_archetypes<'w>(
+        &'w self,
+        type_ids: &'w [TypeId],
+    ) -> Box<dyn Iterator<Item = DynArchetype<'w, Self::Entity>> + 'w> {
+        let columns: Option<Rc<[_]>> = type_ids
+            .iter()
+            .map(|&type_id| self.columns.dyn_column(type_id))
+            .collect();
+
+        Box::new(columns.into_iter().map(|columns| DynArchetype {
+            len: self.len(),
+            to_id: Box::new(|index| Id::new(EntityKey::new_unchecked(*self.ids.get(index)))),
+            columns,
+        }))
+    }
+
+    unsafe fn drain_filter<'w, F, P, O>(&mut self, predicate: &mut P, out: &mut O)
+    where
+        F: Fetch + 'w,
+        P: FnMut(F::Item<'w>) -> bool,
+        O: FnMut(Id<Self::Entity>, Self::Entity),
+    {
+        let Some(fetch) = F::new(&self.ids, &self.columns) else {
+            return;
+        };
+
+        let kept = self.partition_impl(|_, index| {
+            // Safety: `partition_impl` visits each entity once without
+            // reallocating, and the caller ensures that the item does not
+            // outlive the call.
+            !predicate(unsafe { fetch.get(index) })
+        });
+
+        for index in (kept..self.ids.len()).rev() {
+            let id = self.ids.remove(index);
+            let entity = self.columns.remove(index);
+
+            self.indices.remove(id);
+
+            out(Id::new(EntityKey::new_unchecked(id)), entity);
+        }
+    }
+
     fn fetch<'w, F>(&'w self) -> Self::Fetch<'w, F>
     where
         F: Fetch + 'w,
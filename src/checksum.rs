@@ -0,0 +1,142 @@
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+
+use derivative::Derivative;
+use fxhash::FxHasher64;
+
+use crate::{Entity, Id};
+
+/// Hashes a single entity together with its `Id`.
+///
+/// `FxHasher64` is deterministic, so the result can be compared across
+/// machines, as long as the components' `Hash` impls are deterministic too.
+/// Note that the std impls for `str`, slices and `Vec` hash their length as a
+/// `usize`, so components containing them only hash the same on peers with
+/// the same word size.
+pub(crate) fn entity_checksum<E: Entity>(id: Id<E>, entity: impl Hash) -> u64 {
+    let mut hasher = FxHasher64::default();
+    id.hash(&mut hasher);
+    entity.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Per-entity checksums of a [`World`](crate::World), used to find out which
+/// entities differ between two worlds.
+///
+/// These are typically only exchanged after [`World::checksum`] has detected
+/// a desync.
+///
+/// [`World::checksum`]: crate::World::checksum
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), Default(bound = ""))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "Id<E>: serde::Serialize",
+        deserialize = "Id<E>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Checksums<E: Entity>(BTreeMap<Id<E>, u64>);
+
+impl<E: Entity> Checksums<E> {
+    pub(crate) fn insert(&mut self, id: Id<E>, checksum: u64) {
+        self.0.insert(id, checksum);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, id: Id<E>) -> Option<u64> {
+        self.0.get(&id).copied()
+    }
+
+    /// Iterates over the checksums, ordered by `Id`.
+    pub fn iter(&self) -> impl Iterator<Item = (Id<E>, u64)> + '_ {
+        self.0.iter().map(|(&id, &checksum)| (id, checksum))
+    }
+
+    /// Returns the same value as [`World::checksum`] would for the world that
+    /// these checksums were computed from.
+    ///
+    /// [`World::checksum`]: crate::World::checksum
+    pub fn total(&self) -> u64 {
+        combine(self.0.values().copied())
+    }
+
+    /// Returns all entities that differ between `self` and `other`, ordered by
+    /// `Id`.
+    pub fn divergences<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = Divergence<E>> + 'a {
+        let ours = self
+            .0
+            .iter()
+            .filter_map(|(&id, &checksum)| match other.get(id) {
+                None => Some(Divergence::OnlyInSelf(id)),
+                Some(other_checksum) if other_checksum != checksum => Some(Divergence::Changed(id)),
+                Some(_) => None,
+            });
+        let theirs = other
+            .0
+            .keys()
+            .filter(|&&id| !self.0.contains_key(&id))
+            .map(|&id| Divergence::OnlyInOther(id));
+
+        let mut divergences: Vec<_> = ours.chain(theirs).collect();
+        divergences.sort_by_key(|divergence| divergence.id());
+
+        divergences.into_iter()
+    }
+}
+
+/// An entity that differs between two worlds. The entity's variant can be
+/// obtained from its `Id`.
+#[derive(Derivative)]
+#[derivative(
+    Copy(bound = ""),
+    Clone(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    Debug(bound = "")
+)]
+pub enum Divergence<E: Entity> {
+    /// The entity only exists in the first world.
+    OnlyInSelf(Id<E>),
+
+    /// The entity only exists in the second world.
+    OnlyInOther(Id<E>),
+
+    /// The entity exists in both worlds, but its components differ.
+    Changed(Id<E>),
+}
+
+impl<E: Entity> Divergence<E> {
+    pub fn id(self) -> Id<E> {
+        match self {
+            Divergence::OnlyInSelf(id) | Divergence::OnlyInOther(id) | Divergence::Changed(id) => {
+                id
+            }
+        }
+    }
+}
+
+/// Combines per-entity checksums in a way that does not depend on their order,
+/// so that storage order does not affect the result.
+pub(crate) fn combine(checksums: impl Iterator<Item = u64>) -> u64 {
+    let (len, sum) = checksums.fold((0u64, 0u64), |(len, sum), checksum| {
+        (len + 1, sum.wrapping_add(checksum))
+    });
+
+    let mut hasher = FxHasher64::default();
+    len.hash(&mut hasher);
+    sum.hash(&mut hasher);
+
+    hasher.finish()
+}
@@ -1,22 +1,49 @@
-use std::{any::Any, cell::UnsafeCell};
+use std::{
+    any::Any,
+    cell::UnsafeCell,
+    ops::Range,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::Component;
 
+/// Source of the versions that identify the contents of chunks. Versions are
+/// unique across all columns, so that equal versions imply equal contents.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Number of components per chunk for change tracking.
+const CHUNK_LEN: usize = 64;
+
+/// Storage for one component type of an archetype.
+///
+/// Besides the components, a column tracks a version for each chunk of
+/// `CHUNK_LEN` components that identifies its current contents. Handing out
+/// mutable access to a component resets the version of its chunk to zero,
+/// meaning "unknown". Fresh versions are assigned lazily in
+/// [`Column::clone_from_changed`], which uses them to copy only the chunks
+/// that have changed.
 #[derive(Debug)]
-pub struct Column<C>(UnsafeCell<Vec<C>>);
+pub struct Column<C>(UnsafeCell<Vec<C>>, Vec<AtomicU64>);
 
 unsafe impl<C: Send> Send for Column<C> {}
 unsafe impl<C: Sync> Sync for Column<C> {}
 
 impl<C: Clone> Clone for Column<C> {
     fn clone(&self) -> Self {
-        Self(UnsafeCell::new(self.borrow().clone()))
+        Self(
+            UnsafeCell::new(self.borrow().clone()),
+            self.1
+                .iter()
+                .map(|version| AtomicU64::new(version.load(Ordering::Relaxed)))
+                .collect(),
+        )
     }
 }
 
 impl<C> Default for Column<C> {
     fn default() -> Self {
-        Self(Default::default())
+        Self(Default::default(), Default::default())
     }
 }
 
@@ -32,6 +59,7 @@ impl<C> Column<C> {
         unsafe { &*ptr }
     }
 
+    /// Callers that change components have to call `changed` afterwards.
     fn borrow_mut(&mut self) -> &mut Vec<C> {
         let ptr = self.0.get();
 
@@ -39,6 +67,37 @@ impl<C> Column<C> {
         unsafe { &mut *ptr }
     }
 
+    /// Resets the versions of the chunks overlapping `range`, after adjusting
+    /// the number of chunks to our length.
+    fn changed(&mut self, range: Range<usize>) {
+        let chunks = self.borrow().len().div_ceil(CHUNK_LEN);
+        self.1.resize_with(chunks, Default::default);
+
+        let end = range.end.div_ceil(CHUNK_LEN).min(chunks);
+        let start = (range.start / CHUNK_LEN).min(end);
+
+        for version in &mut self.1[start..end] {
+            *version.get_mut() = 0;
+        }
+    }
+
+    /// Returns the version of the current contents of `chunk`, assigning a
+    /// fresh one if it has been mutated since the last call.
+    fn chunk_version(&self, chunk: usize) -> u64 {
+        let version = self.1[chunk].load(Ordering::Relaxed);
+
+        if version != 0 {
+            return version;
+        }
+
+        let fresh = NEXT_VERSION.fetch_add(1, Ordering::Relaxed);
+
+        match self.1[chunk].compare_exchange(0, fresh, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => fresh,
+            Err(version) => version,
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.borrow().len()
     }
@@ -47,12 +106,27 @@ impl<C> Column<C> {
         self.len() == 0
     }
 
+    pub fn capacity(&self) -> usize {
+        self.borrow().capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.borrow_mut().reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.borrow_mut().shrink_to_fit();
+    }
+
     pub fn get(&self, index: usize) -> &C {
         &self.borrow()[index]
     }
 
     pub fn push(&mut self, component: C) {
+        let index = self.len();
+
         self.borrow_mut().push(component);
+        self.changed(index..index + 1);
     }
 
     pub fn remove(&mut self, index: usize) -> C {
@@ -63,7 +137,39 @@ impl<C> Column<C> {
         let last = inner.len() - 1;
 
         inner.swap(index, last);
-        inner.pop().unwrap()
+        let component = inner.pop().unwrap();
+
+        self.changed(index..index + 1);
+        self.changed(last..last + 1);
+
+        component
+    }
+
+    /// Removes the component at `index`, shifting all following components
+    /// to the left.
+    pub fn shift_remove(&mut self, index: usize) -> C {
+        let len = self.len();
+        let component = self.borrow_mut().remove(index);
+        self.changed(index..len);
+
+        component
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.borrow_mut().swap(a, b);
+        self.changed(a..a + 1);
+        self.changed(b..b + 1);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len();
+
+        self.borrow_mut().truncate(len);
+        self.changed(len..old_len);
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
     }
 
     pub fn last(&self) -> Option<&C> {
@@ -74,6 +180,44 @@ impl<C> Column<C> {
         self.borrow().as_slice()
     }
 
+    /// Makes our contents equal to those of `source`, copying only the chunks
+    /// that differ according to their versions. Returns `true` if anything
+    /// was copied.
+    pub fn clone_from_changed(&mut self, source: &Self) -> bool
+    where
+        C: Clone,
+    {
+        let len = source.len();
+        let mut copied = len < self.len();
+
+        self.truncate(len);
+        self.1
+            .resize_with(len.div_ceil(CHUNK_LEN), Default::default);
+
+        for chunk in 0..self.1.len() {
+            let version = source.chunk_version(chunk);
+
+            // Equal versions imply equal contents, including the number of
+            // components in the chunk. Since we go through the chunks in
+            // order, all components up to this chunk are present.
+            if *self.1[chunk].get_mut() == version {
+                continue;
+            }
+
+            let range = chunk * CHUNK_LEN..((chunk + 1) * CHUNK_LEN).min(len);
+            let inner = self.borrow_mut();
+            let overlap = range.start..range.end.min(inner.len());
+
+            inner[overlap.clone()].clone_from_slice(&source.borrow()[overlap.clone()]);
+            inner.extend_from_slice(&source.borrow()[overlap.end..range.end]);
+
+            *self.1[chunk].get_mut() = version;
+            copied = true;
+        }
+
+        copied
+    }
+
     pub fn into_vec(self) -> Vec<C> {
         self.0.into_inner()
     }
@@ -94,6 +238,7 @@ impl<C> Column<C> {
         ColumnRawPartsMut {
             ptr: inner.as_mut_ptr(),
             len: inner.len(),
+            versions: ChunkVersions(self.1.as_ptr()),
         }
     }
 }
@@ -111,9 +256,23 @@ impl<C> Clone for ColumnRawParts<C> {
 
 impl<C> Copy for ColumnRawParts<C> {}
 
+impl<C> ColumnRawParts<C> {
+    /// Returns raw parts for `len` instances of the zero-sized tag component
+    /// `C`. Tags need no storage, so the pointer is dangling.
+    pub fn tag(len: usize) -> Self {
+        assert_eq!(size_of::<C>(), 0, "Tag components must be zero-sized");
+
+        Self {
+            ptr: NonNull::dangling().as_ptr(),
+            len,
+        }
+    }
+}
+
 pub struct ColumnRawPartsMut<C> {
     pub ptr: *mut C,
     pub len: usize,
+    pub versions: ChunkVersions,
 }
 
 impl<C> Clone for ColumnRawPartsMut<C> {
@@ -124,6 +283,63 @@ impl<C> Clone for ColumnRawPartsMut<C> {
 
 impl<C> Copy for ColumnRawPartsMut<C> {}
 
+impl<C> ColumnRawPartsMut<C> {
+    /// See [`ColumnRawParts::tag`].
+    pub fn tag(len: usize) -> Self {
+        assert_eq!(size_of::<C>(), 0, "Tag components must be zero-sized");
+
+        Self {
+            ptr: NonNull::dangling().as_ptr(),
+            len,
+            versions: ChunkVersions::none(),
+        }
+    }
+
+    /// Returns a pointer to the component at `index` for writing, resetting
+    /// the version of its chunk.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than `len`, and the column must not have been
+    /// modified since these raw parts were created.
+    #[inline]
+    pub unsafe fn get_ptr(&self, index: usize) -> *mut C {
+        debug_assert!(index < self.len);
+
+        // Safety: Forwarded to the caller.
+        unsafe {
+            self.versions.changed(index);
+            self.ptr.add(index)
+        }
+    }
+}
+
+/// The chunk versions of a column, which have to be reset when a component is
+/// handed out for writing.
+#[derive(Clone, Copy)]
+pub struct ChunkVersions(*const AtomicU64);
+
+impl ChunkVersions {
+    /// Versions for columns that do not track changes, such as tags.
+    pub fn none() -> Self {
+        Self(ptr::null())
+    }
+
+    /// Resets the version of the chunk containing the component at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the column, and the column
+    /// must not have been modified since `self` was created.
+    #[inline]
+    pub unsafe fn changed(&self, index: usize) {
+        if !self.0.is_null() {
+            // Safety: Forwarded to the caller.
+            unsafe { &*self.0.add(index / CHUNK_LEN) }.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
 // For proc macros.
 #[doc(hidden)]
 pub fn downcast_ref<C: Component, D: Component>(column: &Column<C>) -> Option<&Column<D>> {
@@ -0,0 +1,113 @@
+use derivative::Derivative;
+
+use crate::{
+    entity::EntityVariant, CloneEntityFromRef, Entity, EntityRef, EntityRefMut, Id, World,
+};
+
+/// Entities whose changes can be described component by component.
+///
+/// This is implemented by `derive(Entity)` if all components implement
+/// `Clone` and `PartialEq`. For structs, `Diff` has one `Option` per field,
+/// which is `Some` if the field changed. Flattened fields are replaced as a
+/// whole. For enums, `Diff` is an enum of the variants' `Diff`s.
+///
+/// Use `#[stecs(derive_diff(...))]` to derive traits such as
+/// `serde::Serialize` for the generated `Diff` type.
+pub trait EntityDiff: Entity {
+    type Diff;
+
+    /// Returns the components that differ between `old` and `new`, or `None`
+    /// if they are equal.
+    fn diff(old: EntityRef<'_, Self>, new: EntityRef<'_, Self>) -> Option<Self::Diff>;
+
+    fn apply_diff(entity: EntityRefMut<'_, Self>, diff: Self::Diff);
+}
+
+/// The difference between two worlds, as computed by [`World::diff`].
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "E: Clone, E::Diff: Clone"),
+    Debug(bound = "E: std::fmt::Debug, E::Diff: std::fmt::Debug"),
+    Default(bound = "")
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "Id<E>: serde::Serialize, E: serde::Serialize, E::Diff: serde::Serialize",
+        deserialize = "Id<E>: serde::Deserialize<'de>, E: serde::Deserialize<'de>, \
+                       E::Diff: serde::Deserialize<'de>"
+    ))
+)]
+pub struct WorldDiff<E: EntityDiff> {
+    /// Entities that only exist in the new world, ordered by `Id`.
+    pub spawned: Vec<(Id<E>, E)>,
+
+    /// Entities that only exist in the old world, ordered by `Id`.
+    pub despawned: Vec<Id<E>>,
+
+    /// Entities whose components differ, ordered by `Id`.
+    pub changed: Vec<(Id<E>, E::Diff)>,
+}
+
+impl<E: EntityDiff> WorldDiff<E> {
+    pub fn is_empty(&self) -> bool {
+        self.spawned.is_empty() && self.despawned.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<E> World<E>
+where
+    E: EntityDiff + EntityVariant<E> + CloneEntityFromRef,
+{
+    /// Computes the entities that were spawned, despawned or changed between
+    /// `old` and `new`.
+    pub fn diff(old: &Self, new: &Self) -> WorldDiff<E> {
+        let mut diff = WorldDiff::default();
+
+        for (id, old_entity) in old.entities() {
+            match new.entity::<E>(id) {
+                Some(new_entity) => {
+                    if let Some(entity_diff) = E::diff(old_entity, new_entity) {
+                        diff.changed.push((id, entity_diff));
+                    }
+                }
+                None => diff.despawned.push(id),
+            }
+        }
+
+        for (id, new_entity) in new.entities() {
+            if !old.contains(id) {
+                diff.spawned
+                    .push((id, E::clone_entity_from_ref(new_entity)));
+            }
+        }
+
+        diff.spawned.sort_by_key(|(id, _)| *id);
+        diff.despawned.sort();
+        diff.changed.sort_by_key(|(id, _)| *id);
+
+        diff
+    }
+
+    /// Applies a diff that was computed with [`World::diff`], turning the old
+    /// world into the new world.
+    ///
+    /// Spawned entities keep their `Id`s. Changes to entities that do not
+    /// exist in `self` are ignored.
+    pub fn apply_diff(&mut self, diff: WorldDiff<E>) {
+        for id in diff.despawned {
+            self.despawn(id);
+        }
+
+        for (id, entity) in diff.spawned {
+            self.spawn_at(id, entity);
+        }
+
+        for (id, entity_diff) in diff.changed {
+            if let Some(entity) = self.entity_mut::<E>(id) {
+                E::apply_diff(entity, entity_diff);
+            }
+        }
+    }
+}
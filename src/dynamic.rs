@@ -0,0 +1,292 @@
+use std::{
+    any::{Any, TypeId},
+    rc::Rc,
+};
+
+use crate::{
+    column::Column, query::assert_unique_borrows, stats::ColumnStats, Component, Entity, Id, World,
+    WorldData,
+};
+
+/// Type-erased access to a [`Column`].
+pub trait AnyColumn {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn stats(&self) -> ColumnStats;
+
+    fn get_any(&self, index: usize) -> &dyn Any;
+
+    /// # Safety
+    ///
+    /// The caller has to ensure that the returned reference does not alias any
+    /// other borrows of the same component.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_any_mut_unchecked(&self, index: usize) -> &mut dyn Any;
+}
+
+impl<C: Component> AnyColumn for Column<C> {
+    fn len(&self) -> usize {
+        Column::len(self)
+    }
+
+    fn stats(&self) -> ColumnStats {
+        ColumnStats::new::<C, C>(Column::len(self), self.capacity())
+    }
+
+    fn get_any(&self, index: usize) -> &dyn Any {
+        self.get(index)
+    }
+
+    unsafe fn get_any_mut_unchecked(&self, index: usize) -> &mut dyn Any {
+        let parts = self.as_raw_parts_mut();
+        assert!(index < parts.len);
+
+        // Safety: Forwarded to the caller.
+        unsafe { &mut *parts.get_ptr(index) }
+    }
+}
+
+/// A query whose components are only known at runtime, e.g. for scripting.
+///
+/// Borrows are checked when the query is executed, with the same rules as for
+/// static queries: a component may be borrowed mutably only once.
+#[derive(Clone, Debug, Default)]
+pub struct DynQuery {
+    borrows: Vec<(TypeId, bool)>,
+    type_ids: Vec<TypeId>,
+}
+
+impl DynQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(mut self, type_id: TypeId) -> Self {
+        self.borrows.push((type_id, false));
+        self.type_ids.push(type_id);
+        self
+    }
+
+    pub fn write(mut self, type_id: TypeId) -> Self {
+        self.borrows.push((type_id, true));
+        self.type_ids.push(type_id);
+        self
+    }
+
+    /// The borrowed component types, together with whether they are borrowed
+    /// mutably.
+    pub fn borrows(&self) -> &[(TypeId, bool)] {
+        &self.borrows
+    }
+
+    fn assert_borrow(&self) {
+        assert_unique_borrows(|f| {
+            for &(type_id, unique) in &self.borrows {
+                f(type_id, unique);
+            }
+        });
+    }
+}
+
+/// An archetype that has all components of a [`DynQuery`].
+#[doc(hidden)]
+pub struct DynArchetype<'w, E: Entity> {
+    pub len: usize,
+    pub to_id: Box<dyn Fn(usize) -> Id<E> + 'w>,
+    pub columns: Rc<[&'w dyn AnyColumn]>,
+}
+
+impl<'w, E: Entity + 'w> DynArchetype<'w, E> {
+    pub fn map_id<F: Entity>(self, f: fn(Id<E>) -> Id<F>) -> DynArchetype<'w, F> {
+        let to_id = self.to_id;
+
+        DynArchetype {
+            len: self.len,
+            to_id: Box::new(move |index| f(to_id(index))),
+            columns: self.columns,
+        }
+    }
+}
+
+/// The components of one entity matched by [`World::dyn_query`], in the
+/// order of the query.
+#[derive(Clone)]
+pub struct DynRow<'a> {
+    columns: Rc<[&'a dyn AnyColumn]>,
+    index: usize,
+}
+
+impl<'a> DynRow<'a> {
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&'a dyn Any> {
+        Some(self.columns.get(i)?.get_any(self.index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a dyn Any> + '_ {
+        self.columns.iter().map(|column| column.get_any(self.index))
+    }
+}
+
+/// The components of one entity matched by [`World::dyn_query_mut`], in the
+/// order of the query.
+pub struct DynRowMut<'a> {
+    columns: Rc<[&'a dyn AnyColumn]>,
+    borrows: &'a [(TypeId, bool)],
+    index: usize,
+}
+
+impl<'a> DynRowMut<'a> {
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&dyn Any> {
+        Some(self.columns.get(i)?.get_any(self.index))
+    }
+
+    /// Returns `None` if the component was borrowed immutably.
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut dyn Any> {
+        let (_, unique) = *self.borrows.get(i)?;
+
+        // Safety: Each entity is visited only once, we checked that unique
+        // borrows do not alias, and `self` is borrowed mutably.
+        unique.then(|| unsafe { self.columns[i].get_any_mut_unchecked(self.index) })
+    }
+
+    pub fn into_components(self) -> impl Iterator<Item = DynComponent<'a>> {
+        (0..self.columns.len()).map(move |i| {
+            let column = self.columns[i];
+
+            if self.borrows[i].1 {
+                // Safety: Each entity is visited only once, we checked that
+                // unique borrows do not alias, and `self` is consumed.
+                DynComponent::Mut(unsafe { column.get_any_mut_unchecked(self.index) })
+            } else {
+                DynComponent::Ref(column.get_any(self.index))
+            }
+        })
+    }
+}
+
+/// A component borrowed by a [`DynQuery`].
+pub enum DynComponent<'a> {
+    Ref(&'a dyn Any),
+    Mut(&'a mut dyn Any),
+}
+
+impl<'a> DynComponent<'a> {
+    pub fn as_any(&self) -> &dyn Any {
+        match self {
+            DynComponent::Ref(component) => *component,
+            DynComponent::Mut(component) => &**component,
+        }
+    }
+
+    /// Returns `None` if the component was borrowed immutably.
+    pub fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        match self {
+            DynComponent::Ref(_) => None,
+            DynComponent::Mut(component) => Some(&mut **component),
+        }
+    }
+}
+
+impl<E: Entity> World<E> {
+    pub fn dyn_get(&self, id: Id<E>, type_id: TypeId) -> Option<&dyn Any> {
+        let (column, index) = self.0.dyn_column(id, type_id)?;
+
+        Some(column.get_any(index))
+    }
+
+    pub fn dyn_get_mut(&mut self, id: Id<E>, type_id: TypeId) -> Option<&mut dyn Any> {
+        let (column, index) = self.0.dyn_column(id, type_id)?;
+
+        // Safety: We hold an exclusive borrow of the world.
+        Some(unsafe { column.get_any_mut_unchecked(index) })
+    }
+
+    /// Iterates over the entities that have all components of `query`, in the
+    /// same order as static queries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `query` borrows any component mutably.
+    pub fn dyn_query<'a>(
+        &'a self,
+        query: &'a DynQuery,
+    ) -> impl Iterator<Item = (Id<E>, DynRow<'a>)> + 'a {
+        assert!(
+            query.borrows.iter().all(|&(_, unique)| !unique),
+            "dyn_query must not borrow mutably, use dyn_query_mut instead",
+        );
+
+        self.0
+            .dyn_archetypes(&query.type_ids)
+            .flat_map(|archetype| {
+                let DynArchetype {
+                    len,
+                    to_id,
+                    columns,
+                } = archetype;
+
+                (0..len).map(move |index| {
+                    let row = DynRow {
+                        columns: columns.clone(),
+                        index,
+                    };
+
+                    (to_id(index), row)
+                })
+            })
+    }
+
+    /// Iterates over the entities that have all components of `query`, in the
+    /// same order as static queries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `query` violates Rust's borrowing rules.
+    pub fn dyn_query_mut<'a>(
+        &'a mut self,
+        query: &'a DynQuery,
+    ) -> impl Iterator<Item = (Id<E>, DynRowMut<'a>)> + 'a {
+        // Safety: Check that the query does not specify borrows that violate
+        // Rust's borrowing rules.
+        query.assert_borrow();
+
+        self.0
+            .dyn_archetypes(&query.type_ids)
+            .flat_map(move |archetype| {
+                let DynArchetype {
+                    len,
+                    to_id,
+                    columns,
+                } = archetype;
+
+                (0..len).map(move |index| {
+                    let row = DynRowMut {
+                        columns: columns.clone(),
+                        borrows: &query.borrows,
+                        index,
+                    };
+
+                    (to_id(index), row)
+                })
+            })
+    }
+}
@@ -1,21 +1,68 @@
-use std::{any::Any, fmt::Debug, hash::Hash};
+use std::{
+    any::{Any, TypeId},
+    error::Error,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    str::FromStr,
+};
 
 use derivative::Derivative;
 
 use crate::{
-    archetype::EntityKey, column::Column, query::fetch::Fetch, Component, Query, QueryShared,
-    WorldData,
+    archetype::{Archetype, EntityKey},
+    column::Column,
+    dynamic::AnyColumn,
+    layout::EntityLayout,
+    query::fetch::Fetch,
+    Component, Query, QueryShared, WorldData,
 };
 
 pub trait Columns: Default + 'static {
     type Entity: Entity<Id = EntityKey<Self::Entity>> + EntityVariant<Self::Entity>;
 
+    type IntoIter: Iterator<Item = Self::Entity>;
+
+    /// Whether despawning keeps the remaining entities in spawn order. This is
+    /// set through `#[stecs(ordered)]`.
+    const ORDERED: bool = false;
+
     fn column<C: Component>(&self) -> Option<&Column<C>>;
 
+    /// Whether the entity has the zero-sized component `C` as a tag, declared
+    /// through `#[stecs(tag)]`. Tags have no column, so they have to implement
+    /// `Default` to be recreated when an entity is moved out of the world.
+    fn has_tag<C: Component>() -> bool;
+
+    /// Returns `self` if it is a `T`, or else the columns of the first
+    /// sub-entity that is flattened into us (possibly indirectly) and stored in
+    /// a `T`. This lets queries for flattened entities match us.
+    fn flat_columns<T: Columns>(&self) -> Option<&T>;
+
+    fn dyn_column(&self, type_id: TypeId) -> Option<&dyn AnyColumn>;
+
+    /// Calls `f` for each component column, passing its field path.
+    fn for_each_column(&self, f: &mut dyn FnMut(&str, &dyn AnyColumn));
+
     fn push(&mut self, entity: Self::Entity);
 
     fn remove(&mut self, index: usize) -> Self::Entity;
 
+    fn shift_remove(&mut self, index: usize) -> Self::Entity;
+
+    fn swap(&mut self, a: usize, b: usize);
+
+    fn truncate(&mut self, len: usize);
+
+    fn reserve(&mut self, additional: usize);
+
+    fn shrink_to_fit(&mut self);
+
+    /// Moves the entities out of the columns, in storage order.
+    fn into_iter(self) -> Self::IntoIter;
+
+    /// Describes the entity type, given the number of entities `len`.
+    fn describe(len: usize) -> EntityLayout;
+
     #[doc(hidden)]
     fn new_fetch<'a>(&self, len: usize) -> <Self::Entity as Entity>::Fetch<'a>;
 
@@ -24,7 +71,16 @@ pub trait Columns: Default + 'static {
 }
 
 pub trait Entity: Sized + 'static {
-    type Id: Copy + Debug + Eq + Ord + Hash + 'static;
+    type Id: Copy
+        + Debug
+        + Display
+        + FromStr<Err = ParseIdError>
+        + Eq
+        + Ord
+        + Hash
+        + IdBits
+        + NamedId
+        + 'static;
 
     type Borrow<'a>: QueryShared + Clone;
 
@@ -50,15 +106,38 @@ pub trait CloneEntityIntoRef: EntityStruct {
     fn clone_entity_into_ref(&self, target: &mut Self::BorrowMut<'_>);
 }
 
-pub trait EntityStruct: Entity {
+pub trait EntityStruct: Entity<WorldData = Archetype<<Self as EntityStruct>::Columns>> {
     type Columns: Columns<Entity = Self>;
 }
 
+/// Entity enums, whose variants are named by a generated `Kind` enum without
+/// payloads, e.g. `EntityKind::Player` for `Entity::Player(Player)`.
+pub trait EntityEnum: Entity {
+    type Kind: Copy + Debug + Display + Eq + Ord + Hash + 'static;
+
+    /// Returns the variant that `id` belongs to.
+    fn kind(id: Self::Id) -> Self::Kind;
+}
+
 pub trait EntityVariant<EOuter: Entity>: Entity {
     fn into_outer(self) -> EOuter;
 
     fn spawn(self, data: &mut EOuter::WorldData) -> Id<Self>;
 
+    fn reserve(data: &mut EOuter::WorldData, additional: usize);
+
+    fn world_data(data: &EOuter::WorldData) -> &Self::WorldData;
+
+    /// Projects a pointer to the outer `WorldData` onto our part of it.
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to a valid `EOuter::WorldData`. The projection does
+    /// not create any intermediate references, so that pointers to disjoint
+    /// variants can be used at the same time.
+    #[doc(hidden)]
+    unsafe fn world_data_ptr(data: *mut EOuter::WorldData) -> *mut Self::WorldData;
+
     fn id_to_outer(id: Self::Id) -> EOuter::Id
     where
         Self: Sized;
@@ -68,6 +147,62 @@ pub trait EntityVariant<EOuter: Entity>: Entity {
         Self: Sized;
 }
 
+/// Conversion of entity ids from and to a stable `u64` representation, e.g.
+/// for passing them through FFI or network packets.
+///
+/// Struct ids use the bits of their `thunderdome::Index`, with the generation
+/// in the upper and the slot in the lower 32 bits. Enum ids additionally encode
+/// the variant in the topmost bits of the slot, with the outermost enum taking
+/// the lowest of these bits.
+pub trait IdBits: Sized {
+    /// The number of topmost slot bits that are used for encoding variants.
+    const VARIANT_BITS: u32;
+
+    /// # Panics
+    ///
+    /// Panics if the slot is too large to leave room for encoding the
+    /// variant, i.e. if it does not fit into the lower
+    /// `32 - Self::VARIANT_BITS` bits.
+    fn to_bits(self) -> u64;
+
+    /// Returns `None` if the bits do not encode a valid id. Note that this
+    /// does not check if the id belongs to an existing entity; use
+    /// [`World::validate_id`] for that.
+    ///
+    /// [`World::validate_id`]: crate::World::validate_id
+    fn from_bits(bits: u64) -> Option<Self>;
+}
+
+/// Formatting of ids for `Display` and `FromStr`, under the name of the enum
+/// variant that contains the entity.
+#[doc(hidden)]
+pub trait NamedId: Sized {
+    /// Formats the id with `name`, unless the id is an enum id, which names
+    /// its variant itself.
+    fn fmt_named(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Parses an id that was formatted by `fmt_named` with the same `name`.
+    fn parse_named(s: &str, name: &str) -> Option<Self>;
+}
+
+/// The error returned when parsing an `Id` from a string fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseIdError(String);
+
+impl ParseIdError {
+    pub fn new(input: &str) -> Self {
+        Self(input.to_string())
+    }
+}
+
+impl Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid entity id `{}`", self.0)
+    }
+}
+
+impl Error for ParseIdError {}
+
 pub type EntityRef<'a, E> = <E as Entity>::Borrow<'a>;
 
 pub type EntityRefMut<'a, E> = <E as Entity>::BorrowMut<'a>;
@@ -116,6 +251,44 @@ impl<E: Entity> Id<E> {
     {
         EInner::try_id_from_outer(self.0).map(Id::new)
     }
+
+    /// Returns the variant that we belong to. This is encoded in the id, so no
+    /// world lookup is needed.
+    pub fn kind(self) -> E::Kind
+    where
+        E: EntityEnum,
+    {
+        E::kind(self.0)
+    }
+
+    /// See [`IdBits`] for the encoding.
+    pub fn to_bits(self) -> u64 {
+        self.0.to_bits()
+    }
+
+    /// See [`IdBits`] for the encoding.
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        E::Id::from_bits(bits).map(Self)
+    }
+}
+
+/// Formats ids compactly, e.g. as `Enemy#12v3` for an `Enemy` in slot 12 with
+/// generation 3. Enum ids are formatted with the name of the innermost enum
+/// variant that contains the entity, and struct ids with the name of the
+/// struct, leaving out generic arguments.
+impl<E: Entity> Display for Id<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Parses ids in the format produced by `Display`.
+impl<E: Entity> FromStr for Id<E> {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        E::Id::from_str(s).map(Self)
+    }
 }
 
 // For proc macros.
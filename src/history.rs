@@ -0,0 +1,267 @@
+use crate::{entity::EntityVariant, CloneEntityFromRef, Entity, EntityRefMut, Id, World};
+
+/// A [`World`] wrapper that records edits, so that they can be undone and
+/// redone.
+///
+/// Edits are recorded as the operations that invert them, capturing previous
+/// entity state with [`CloneEntityFromRef`]. Despawned and replaced entities
+/// are restored with [`World::spawn_at`], so they get their original `Id`
+/// back. Note that restoring an entity may change its position in storage
+/// order.
+///
+/// Each edit forms its own transaction, unless it is made inside of
+/// [`History::group`].
+pub struct History<E: Entity> {
+    world: World<E>,
+    undo: Vec<Vec<Op<E>>>,
+    redo: Vec<Vec<Op<E>>>,
+    group: Option<Vec<Op<E>>>,
+    pending: Option<Pending<E>>,
+}
+
+/// The previous state of an entity handed out by [`History::entity_mut`],
+/// which is only recorded once the entity turns out to have changed.
+struct Pending<E: Entity> {
+    id: Id<E>,
+    previous: E,
+    changed: fn(&World<E>, Id<E>, &E) -> bool,
+}
+
+enum Op<E: Entity> {
+    Despawn(Id<E>),
+    SpawnAt(Id<E>, E),
+}
+
+impl<E> Op<E>
+where
+    E: Entity + EntityVariant<E>,
+{
+    /// Applies the operation, returning the operation that inverts it.
+    fn apply(self, world: &mut World<E>) -> Option<Op<E>> {
+        match self {
+            Op::Despawn(id) => world.despawn(id).map(|entity| Op::SpawnAt(id, entity)),
+            Op::SpawnAt(id, entity) => Some(match world.spawn_at(id, entity) {
+                Some(old) => Op::SpawnAt(id, old),
+                None => Op::Despawn(id),
+            }),
+        }
+    }
+}
+
+impl<E> History<E>
+where
+    E: Entity + EntityVariant<E> + CloneEntityFromRef,
+{
+    pub fn new(world: World<E>) -> Self {
+        Self {
+            world,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            group: None,
+            pending: None,
+        }
+    }
+
+    pub fn world(&self) -> &World<E> {
+        &self.world
+    }
+
+    pub fn into_world(self) -> World<E> {
+        self.world
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+            || self.pending.as_ref().is_some_and(|pending| {
+                (pending.changed)(&self.world, pending.id, &pending.previous)
+            })
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all recorded edits.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+    }
+
+    pub fn spawn<F>(&mut self, entity: F) -> Id<F>
+    where
+        F: EntityVariant<E>,
+    {
+        let id = self.world.spawn(entity);
+        self.record(Op::Despawn(id.to_outer()));
+
+        id
+    }
+
+    pub fn despawn<F>(&mut self, id: Id<F>) -> Option<E>
+    where
+        F: EntityVariant<E>,
+    {
+        let id = id.to_outer();
+        let previous = E::clone_entity_from_ref(self.world.entity::<E>(id)?);
+
+        self.record(Op::SpawnAt(id, previous));
+        self.world.despawn(id)
+    }
+
+    /// Returns a mutable reference to an entity, so that changes to its
+    /// components can be undone.
+    ///
+    /// The entity's current state is kept aside, and only recorded as an edit
+    /// if the entity differs from it when the next edit is made or the history
+    /// is used.
+    pub fn entity_mut<F>(&mut self, id: Id<F>) -> Option<EntityRefMut<'_, F>>
+    where
+        F: EntityVariant<E>,
+        E: PartialEq,
+    {
+        self.flush();
+
+        let outer_id = id.to_outer();
+        let previous = E::clone_entity_from_ref(self.world.entity::<E>(outer_id)?);
+
+        self.pending = Some(Pending {
+            id: outer_id,
+            previous,
+            changed: |world, id, previous| {
+                world
+                    .entity::<E>(id)
+                    .is_none_or(|entity| E::clone_entity_from_ref(entity) != *previous)
+            },
+        });
+
+        self.world.entity_mut(id)
+    }
+
+    /// Records all edits made by `f` as a single transaction. Groups can be
+    /// nested, in which case the outermost group forms the transaction.
+    ///
+    /// If `f` panics, the edits made so far still form a transaction.
+    pub fn group<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.group.is_some() {
+            return f(self);
+        }
+
+        self.flush();
+        self.group = Some(Vec::new());
+
+        let guard = GroupGuard(self);
+
+        f(guard.0)
+    }
+
+    fn end_group(&mut self) {
+        self.flush();
+
+        let ops = self.group.take().unwrap_or_default();
+
+        if !ops.is_empty() {
+            self.undo.push(ops);
+        }
+    }
+
+    /// Undoes the last transaction. Returns `false` if there is nothing to
+    /// undo.
+    pub fn undo(&mut self) -> bool {
+        self.flush();
+
+        let Some(ops) = self.undo.pop() else {
+            return false;
+        };
+
+        let inverse = self.apply(ops);
+        self.redo.push(inverse);
+
+        true
+    }
+
+    /// Redoes the last undone transaction. Returns `false` if there is nothing
+    /// to redo.
+    pub fn redo(&mut self) -> bool {
+        self.flush();
+
+        let Some(ops) = self.redo.pop() else {
+            return false;
+        };
+
+        let inverse = self.apply(ops);
+        self.undo.push(inverse);
+
+        true
+    }
+
+    /// Records the entity handed out by `entity_mut` if it has changed.
+    fn flush(&mut self) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+
+        if (pending.changed)(&self.world, pending.id, &pending.previous) {
+            self.push(Op::SpawnAt(pending.id, pending.previous));
+        }
+    }
+
+    fn record(&mut self, op: Op<E>) {
+        self.flush();
+        self.push(op);
+    }
+
+    fn push(&mut self, op: Op<E>) {
+        self.redo.clear();
+
+        match &mut self.group {
+            Some(ops) => ops.push(op),
+            None => self.undo.push(vec![op]),
+        }
+    }
+
+    /// Applies `ops` in reverse order, returning the operations that invert
+    /// them, again to be applied in reverse order.
+    fn apply(&mut self, mut ops: Vec<Op<E>>) -> Vec<Op<E>> {
+        let mut inverse = Vec::with_capacity(ops.len());
+
+        while let Some(op) = ops.pop() {
+            inverse.extend(op.apply(&mut self.world));
+        }
+
+        inverse
+    }
+}
+
+/// Ends the outermost group of a [`History`], even if the group's closure
+/// panics.
+struct GroupGuard<'a, E>(&'a mut History<E>)
+where
+    E: Entity + EntityVariant<E> + CloneEntityFromRef;
+
+impl<E> Drop for GroupGuard<'_, E>
+where
+    E: Entity + EntityVariant<E> + CloneEntityFromRef,
+{
+    fn drop(&mut self) {
+        self.0.end_group();
+    }
+}
+
+impl<E> Default for History<E>
+where
+    E: Entity + EntityVariant<E> + CloneEntityFromRef,
+{
+    fn default() -> Self {
+        Self::new(World::default())
+    }
+}
+
+impl<E> From<World<E>> for History<E>
+where
+    E: Entity + EntityVariant<E> + CloneEntityFromRef,
+{
+    fn from(world: World<E>) -> Self {
+        Self::new(world)
+    }
+}
@@ -0,0 +1,78 @@
+use std::any::TypeId;
+
+/// A description of an entity type and its storage, as returned by
+/// [`World::describe`](crate::World::describe).
+#[derive(Clone, Debug)]
+pub struct EntityLayout {
+    /// The name of the entity type, without module path and generics.
+    pub name: &'static str,
+
+    /// The full name of the entity type, as given by `std::any::type_name`.
+    pub type_name: &'static str,
+
+    /// The number of entities. For flattened entities, this is the number of
+    /// entities that embed them.
+    pub len: usize,
+
+    pub kind: EntityLayoutKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum EntityLayoutKind {
+    Struct {
+        components: Vec<ComponentLayout>,
+
+        /// Zero-sized components declared with `#[stecs(tag)]`, which take no
+        /// storage.
+        tags: Vec<ComponentLayout>,
+
+        /// Sub-entities embedded with `#[stecs(flat)]`, together with the name
+        /// of their field.
+        flattened: Vec<(&'static str, EntityLayout)>,
+    },
+    Enum {
+        /// The layouts of the variants, together with the name of the variant.
+        variants: Vec<(&'static str, EntityLayout)>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ComponentLayout {
+    pub field: &'static str,
+    pub type_name: &'static str,
+    pub type_id: TypeId,
+    pub size: usize,
+}
+
+impl ComponentLayout {
+    pub fn new<C: 'static>(field: &'static str) -> Self {
+        Self {
+            field,
+            type_name: std::any::type_name::<C>(),
+            type_id: TypeId::of::<C>(),
+            size: std::mem::size_of::<C>(),
+        }
+    }
+}
+
+impl EntityLayout {
+    /// Iterates over the components of all struct entities in this layout,
+    /// including flattened and nested ones. Tags are not included, since they
+    /// are not stored.
+    pub fn components(&self) -> Box<dyn Iterator<Item = &ComponentLayout> + '_> {
+        match &self.kind {
+            EntityLayoutKind::Struct {
+                components,
+                flattened,
+                ..
+            } => Box::new(
+                components
+                    .iter()
+                    .chain(flattened.iter().flat_map(|(_, layout)| layout.components())),
+            ),
+            EntityLayoutKind::Enum { variants } => {
+                Box::new(variants.iter().flat_map(|(_, layout)| layout.components()))
+            }
+        }
+    }
+}
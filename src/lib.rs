@@ -16,10 +16,18 @@ macro_rules! smaller_tuples_too {
 }
 
 pub mod archetype;
+pub mod checksum;
 pub mod column;
+pub mod diff;
+pub mod dynamic;
 pub mod entity;
+pub mod history;
+pub mod layout;
 pub mod query;
 pub mod secondary;
+pub mod snapshot;
+pub mod stats;
+pub mod validate;
 pub mod world;
 
 pub use thunderdome;
@@ -33,7 +41,7 @@ pub use fxhash;
 #[doc(inline)]
 pub use self::{
     entity::{CloneEntityFromRef, CloneEntityIntoRef, Entity, EntityRef, EntityRefMut, Id},
-    query::{Or, Query, QueryShared, With, Without},
+    query::{Kind, Or, Query, QueryShared, With, Without},
     secondary::{query::SecondaryQuery, query::SecondaryQueryShared, world::SecondaryWorld},
     world::{World, WorldData},
 };
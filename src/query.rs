@@ -1,3 +1,4 @@
+pub mod dyn_trait;
 pub mod fetch;
 pub mod iter;
 pub mod join;
@@ -8,18 +9,20 @@ use std::{any::TypeId, marker::PhantomData};
 
 use crate::{
     column::{ColumnRawParts, ColumnRawPartsMut},
-    entity::EntityVariant,
+    entity::{EntityEnum, EntityVariant},
     world::WorldFetch,
     Component, Entity, Id, SecondaryQuery, SecondaryQueryShared, SecondaryWorld, WorldData,
 };
 
 use self::{
-    fetch::{Fetch, OptionFetch, UnitFetch, WithFetch, WithoutFetch},
+    fetch::{Fetch, KindFetch, OptionFetch, UnitFetch, WithFetch, WithoutFetch},
     join::JoinQueryBorrow,
     nest::NestQueryBorrow,
 };
 
-// This is unafe because `for_each_borrow` must match `Fetch`.
+/// # Safety
+///
+/// This is unsafe because `for_each_borrow` must match `Fetch`.
 pub unsafe trait Query {
     type Fetch<'w>: Fetch + 'w;
 
@@ -28,10 +31,12 @@ pub unsafe trait Query {
 
 pub type QueryItem<'w, 'a, Q> = <<Q as Query>::Fetch<'w> as Fetch>::Item<'a>;
 
-// This is unsafe because it must not have any exclusive borrows.
+/// # Safety
+///
+/// This is unsafe because it must not have any exclusive borrows.
 pub unsafe trait QueryShared: Query {}
 
-unsafe impl<'q, C: Component> Query for &'q C {
+unsafe impl<C: Component> Query for &C {
     type Fetch<'w> = ColumnRawParts<C>;
 
     fn for_each_borrow(mut f: impl FnMut(TypeId, bool)) {
@@ -39,9 +44,9 @@ unsafe impl<'q, C: Component> Query for &'q C {
     }
 }
 
-unsafe impl<'q, C: Component> QueryShared for &'q C {}
+unsafe impl<C: Component> QueryShared for &C {}
 
-unsafe impl<'q, C: Component> Query for &'q mut C {
+unsafe impl<C: Component> Query for &mut C {
     type Fetch<'w> = ColumnRawPartsMut<C>;
 
     fn for_each_borrow(mut f: impl FnMut(TypeId, bool)) {
@@ -87,6 +92,82 @@ smaller_tuples_too!(
     tuple_impl, F0, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15
 );
 
+/// Conversion of query items into their read-only counterparts, turning every
+/// `&mut C` into `&C`.
+///
+/// `derive(Query)` implements this for structs with `#[stecs(shared = ...)]`.
+pub trait IntoShared {
+    type Shared;
+
+    fn into_shared(self) -> Self::Shared;
+}
+
+impl<'a, C: ?Sized> IntoShared for &'a C {
+    type Shared = &'a C;
+
+    fn into_shared(self) -> Self::Shared {
+        self
+    }
+}
+
+impl<'a, C: ?Sized> IntoShared for &'a mut C {
+    type Shared = &'a C;
+
+    fn into_shared(self) -> Self::Shared {
+        self
+    }
+}
+
+impl<E: Entity> IntoShared for Id<E> {
+    type Shared = Self;
+
+    fn into_shared(self) -> Self::Shared {
+        self
+    }
+}
+
+impl<Q: IntoShared> IntoShared for Option<Q> {
+    type Shared = Option<Q::Shared>;
+
+    fn into_shared(self) -> Self::Shared {
+        self.map(Q::into_shared)
+    }
+}
+
+impl<L: IntoShared, R: IntoShared> IntoShared for Or<L, R> {
+    type Shared = Or<L::Shared, R::Shared>;
+
+    fn into_shared(self) -> Self::Shared {
+        self.map(L::into_shared, R::into_shared)
+    }
+}
+
+macro_rules! share_impl {
+    () => {
+        impl IntoShared for () {
+            type Shared = ();
+
+            fn into_shared(self) -> Self::Shared {}
+        }
+    };
+    ($($name: ident),*) => {
+        impl<$($name: IntoShared,)*> IntoShared for ($($name,)*) {
+            type Shared = ($($name::Shared,)*);
+
+            fn into_shared(self) -> Self::Shared {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+
+                ($($name.into_shared(),)*)
+            }
+        }
+    };
+}
+
+smaller_tuples_too!(
+    share_impl, F0, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15
+);
+
 pub struct With<Q, R>(PhantomData<(Q, R)>);
 
 unsafe impl<Q, R> Query for With<Q, R>
@@ -129,6 +210,18 @@ where
 {
 }
 
+/// Yields the [`EntityEnum::Kind`] of each entity, i.e. the variant of `E` that
+/// it belongs to.
+pub struct Kind<E>(PhantomData<E>);
+
+unsafe impl<E: EntityEnum> Query for Kind<E> {
+    type Fetch<'w> = KindFetch<'w, E>;
+
+    fn for_each_borrow(_: impl FnMut(TypeId, bool)) {}
+}
+
+unsafe impl<E: EntityEnum> QueryShared for Kind<E> {}
+
 // Inspired by `hecs`.
 #[derive(Debug, Clone, Copy)]
 pub enum Or<L, R> {
@@ -434,13 +527,19 @@ where
 
 // Adapted from hecs (https://github.com/Ralith/hecs).
 pub(crate) fn assert_borrow<Q: Query>() {
+    assert_unique_borrows(|f| Q::for_each_borrow(f));
+}
+
+/// Panics if a borrow that is yielded by `for_each_borrow` as unique aliases
+/// any other of its borrows.
+pub(crate) fn assert_unique_borrows(for_each_borrow: impl Fn(&mut dyn FnMut(TypeId, bool))) {
     // This looks like an ugly O(n^2) loop, but everything's constant after inlining, so in
     // practice LLVM optimizes it out entirely.
     let mut i = 0;
-    Q::for_each_borrow(|a, unique| {
+    for_each_borrow(&mut |a, unique| {
         if unique {
             let mut j = 0;
-            Q::for_each_borrow(|b, _| {
+            for_each_borrow(&mut |b, _| {
                 if i != j {
                     core::assert!(a != b, "query violates a unique borrow");
                 }
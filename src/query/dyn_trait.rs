@@ -0,0 +1,224 @@
+//! Queries for trait objects, such as `&dyn Drawable`, that match every entity
+//! with a component implementing the trait. The components are registered with
+//! [`dyn_trait!`](crate::dyn_trait).
+
+use crate::{
+    column::{ChunkVersions, Column},
+    entity::Columns,
+};
+
+use super::fetch::Fetch;
+
+/// A trait object type, such as `dyn Drawable`, whose implementing components
+/// have been registered with [`dyn_trait!`](crate::dyn_trait).
+///
+/// # Safety
+///
+/// The queries for `&Self` and `&mut Self` must borrow all components whose
+/// columns are returned by `raw_parts` and `raw_parts_mut`.
+pub unsafe trait DynTrait: 'static {
+    /// Returns the column of the first registered component that `columns`
+    /// contain, with its components viewed as `Self`.
+    fn raw_parts<T: Columns>(
+        ids: &Column<thunderdome::Index>,
+        columns: &T,
+    ) -> Option<DynRawParts<Self>>;
+
+    /// Like `raw_parts`, but for mutable access.
+    fn raw_parts_mut<T: Columns>(
+        ids: &Column<thunderdome::Index>,
+        columns: &T,
+    ) -> Option<DynRawParts<Self>>;
+}
+
+/// A column whose component type has been erased, such that its components can
+/// only be accessed as the trait object `D`.
+pub struct DynRawParts<D: ?Sized> {
+    pub ptr: *mut (),
+    pub len: usize,
+
+    /// Only used for mutable access.
+    pub versions: ChunkVersions,
+
+    /// Casts `ptr` to the component type and returns the component at the
+    /// given index as `D`.
+    pub get: unsafe fn(*mut (), usize) -> *mut D,
+}
+
+impl<D: ?Sized> Clone for DynRawParts<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: ?Sized> Copy for DynRawParts<D> {}
+
+pub struct DynFetch<D: ?Sized>(DynRawParts<D>);
+
+impl<D: ?Sized> Clone for DynFetch<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: ?Sized> Copy for DynFetch<D> {}
+
+unsafe impl<D> Fetch for DynFetch<D>
+where
+    D: DynTrait + ?Sized,
+{
+    type Item<'a>
+        = &'a D
+    where
+        Self: 'a;
+
+    fn new<T: Columns>(ids: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
+        D::raw_parts(ids, columns).map(Self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len
+    }
+
+    #[inline]
+    unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+    where
+        Self: 'a,
+    {
+        debug_assert!(index < self.len());
+
+        unsafe { &*(self.0.get)(self.0.ptr, index) }
+    }
+}
+
+pub struct DynFetchMut<D: ?Sized>(DynRawParts<D>);
+
+impl<D: ?Sized> Clone for DynFetchMut<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: ?Sized> Copy for DynFetchMut<D> {}
+
+unsafe impl<D> Fetch for DynFetchMut<D>
+where
+    D: DynTrait + ?Sized,
+{
+    type Item<'a>
+        = &'a mut D
+    where
+        Self: 'a;
+
+    fn new<T: Columns>(ids: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
+        D::raw_parts_mut(ids, columns).map(Self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len
+    }
+
+    #[inline]
+    unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+    where
+        Self: 'a,
+    {
+        debug_assert!(index < self.len());
+
+        unsafe {
+            self.0.versions.changed(index);
+            &mut *(self.0.get)(self.0.ptr, index)
+        }
+    }
+}
+
+/// Registers the components that implement a trait, so that `&dyn Trait` and
+/// `&mut dyn Trait` can be used in queries. For example, after
+/// `stecs::dyn_trait!(Drawable: Sprite, Text)`, `world.query::<&dyn Drawable>()`
+/// iterates over all sprites and texts.
+///
+/// The queries match every entity with at least one of the components. If an
+/// entity has several of them, the one listed first is used. Since the
+/// queries borrow all of the listed components, they conflict with other
+/// borrows of any of them.
+///
+/// The components are listed in one place, rather than marked with an
+/// attribute such as `#[stecs(as_dyn(Drawable))]` in each entity, because the
+/// registration becomes a single impl for `dyn Drawable`. Separate derive
+/// invocations cannot see each other to merge their components into it, and
+/// coherence does not allow one impl per invocation. For the same reason, the
+/// macro has to be invoked in the crate that defines the trait: the orphan
+/// rule only allows implementing `Query` for `&dyn Drawable` there. The
+/// components themselves may come from any crate.
+#[macro_export]
+macro_rules! dyn_trait {
+    ($trait: path: $($component: ty),+ $(,)?) => {
+        unsafe impl $crate::query::dyn_trait::DynTrait for dyn $trait {
+            fn raw_parts<T: $crate::entity::Columns>(
+                ids: &$crate::column::Column<$crate::thunderdome::Index>,
+                columns: &T,
+            ) -> ::std::option::Option<$crate::query::dyn_trait::DynRawParts<Self>> {
+                ::std::option::Option::None
+                    $(
+                        .or_else(|| {
+                            <
+                                $crate::column::ColumnRawParts<$component>
+                                as $crate::query::fetch::Fetch
+                            >::new(ids, columns)
+                            .map(|parts| $crate::query::dyn_trait::DynRawParts {
+                                ptr: parts.ptr as *mut (),
+                                len: parts.len,
+                                versions: $crate::column::ChunkVersions::none(),
+                                get: |ptr, index| unsafe {
+                                    (ptr as *mut $component).add(index) as *mut dyn $trait
+                                },
+                            })
+                        })
+                    )+
+            }
+
+            fn raw_parts_mut<T: $crate::entity::Columns>(
+                ids: &$crate::column::Column<$crate::thunderdome::Index>,
+                columns: &T,
+            ) -> ::std::option::Option<$crate::query::dyn_trait::DynRawParts<Self>> {
+                ::std::option::Option::None
+                    $(
+                        .or_else(|| {
+                            <
+                                $crate::column::ColumnRawPartsMut<$component>
+                                as $crate::query::fetch::Fetch
+                            >::new(ids, columns)
+                            .map(|parts| $crate::query::dyn_trait::DynRawParts {
+                                ptr: parts.ptr as *mut (),
+                                len: parts.len,
+                                versions: parts.versions,
+                                get: |ptr, index| unsafe {
+                                    (ptr as *mut $component).add(index) as *mut dyn $trait
+                                },
+                            })
+                        })
+                    )+
+            }
+        }
+
+        unsafe impl<'a> $crate::Query for &'a (dyn $trait + 'a) {
+            type Fetch<'w> = $crate::query::dyn_trait::DynFetch<dyn $trait>;
+
+            fn for_each_borrow(mut f: impl FnMut(::std::any::TypeId, bool)) {
+                $(f(::std::any::TypeId::of::<$component>(), false);)+
+            }
+        }
+
+        unsafe impl<'a> $crate::QueryShared for &'a (dyn $trait + 'a) {}
+
+        unsafe impl<'a> $crate::Query for &'a mut (dyn $trait + 'a) {
+            type Fetch<'w> = $crate::query::dyn_trait::DynFetchMut<dyn $trait>;
+
+            fn for_each_borrow(mut f: impl FnMut(::std::any::TypeId, bool)) {
+                $(f(::std::any::TypeId::of::<$component>(), true);)+
+            }
+        }
+    };
+}
@@ -3,14 +3,17 @@ use std::{any::TypeId, marker::PhantomData};
 use crate::{
     archetype::EntityKey,
     column::{Column, ColumnRawParts, ColumnRawPartsMut},
-    entity::{Columns, EntityStruct},
-    Component, Id,
+    entity::{Columns, EntityEnum, EntityStruct},
+    Component, Entity, Id,
 };
 
 use super::Or;
 
 // TODO: Now that borrow checking is in Query, maybe this no longer needs to be
 // unsafe.
+/// # Safety
+///
+/// `new` must only succeed for columns that contain the data accessed in `get`.
 pub unsafe trait Fetch: Copy {
     type Item<'a>
     where
@@ -46,8 +49,11 @@ where
 {
     type Item<'a> = &'a C where Self: 'a;
 
-    fn new<T: Columns>(_: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
-        columns.column::<C>().map(|column| column.as_raw_parts())
+    fn new<T: Columns>(ids: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
+        columns
+            .column::<C>()
+            .map(|column| column.as_raw_parts())
+            .or_else(|| T::has_tag::<C>().then(|| ColumnRawParts::tag(ids.len())))
     }
 
     #[inline]
@@ -72,10 +78,11 @@ where
 {
     type Item<'a> = &'a mut C where Self: 'a;
 
-    fn new<T: Columns>(_: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
+    fn new<T: Columns>(ids: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
         columns
             .column::<C>()
             .map(|column| column.as_raw_parts_mut())
+            .or_else(|| T::has_tag::<C>().then(|| ColumnRawPartsMut::tag(ids.len())))
     }
 
     #[inline]
@@ -90,7 +97,7 @@ where
     {
         debug_assert!(index < <Self as Fetch>::len(self));
 
-        unsafe { &mut *self.ptr.add(index) }
+        unsafe { &mut *self.get_ptr(index) }
     }
 }
 
@@ -331,6 +338,49 @@ where
     where
         Self: 'a,
     {
-        self.fetch.map_or(None, |fetch| Some(fetch.get(index)))
+        self.fetch.map(|fetch| fetch.get(index))
+    }
+}
+
+pub struct KindFetch<'w, E: Entity> {
+    fetch: E::FetchId<'w>,
+}
+
+impl<E: Entity> Clone for KindFetch<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: Entity> Copy for KindFetch<'_, E> {}
+
+unsafe impl<'w, E> Fetch for KindFetch<'w, E>
+where
+    E: EntityEnum,
+{
+    type Item<'a>
+        = E::Kind
+    where
+        Self: 'a;
+
+    fn new<T: Columns>(ids: &Column<thunderdome::Index>, columns: &T) -> Option<Self> {
+        Some(Self {
+            fetch: E::FetchId::new(ids, columns)?,
+        })
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.fetch.len()
+    }
+
+    #[inline]
+    unsafe fn get<'a>(&self, index: usize) -> Self::Item<'a>
+    where
+        Self: 'a,
+    {
+        let id: Id<E> = unsafe { self.fetch.get(index) };
+
+        id.kind()
     }
 }
@@ -64,7 +64,7 @@ where
 
         JoinQueryFetchIter {
             query_iter,
-            secondary_fetch: self.secondary_fetch.clone(),
+            secondary_fetch: self.secondary_fetch,
         }
     }
 }
@@ -76,6 +76,7 @@ where
     D: WorldData,
 {
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub fn get<'a, E>(
         &'a self,
         id: Id<E>,
@@ -104,6 +105,7 @@ where
     D: WorldData,
 {
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub fn get_mut<'a, E>(
         &'a mut self,
         id: Id<E>,
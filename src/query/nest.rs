@@ -45,6 +45,7 @@ where
     D: WorldData,
 {
     #[inline]
+    #[allow(clippy::type_complexity)]
     pub fn get_mut<'a, E>(
         &'a mut self,
         id: Id<E>,
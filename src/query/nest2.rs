@@ -14,7 +14,6 @@ where
     J1: Fetch + 'w,
     D: WorldData + 'w,
 {
-    pub(crate) data: &'w D,
     pub(crate) ignore_ids: [Id<D::Entity>; 2],
     pub(crate) fetch1: D::Fetch<'w, J1>,
 }
@@ -25,7 +24,6 @@ where
     J1: Fetch + 'w,
     D: WorldData + 'w,
 {
-    pub(crate) data: &'w D,
     pub(crate) ignore_id: Id<D::Entity>,
     pub(crate) fetch0: D::Fetch<'w, J0>,
     pub(crate) fetch1: D::Fetch<'w, J1>,
@@ -38,7 +36,6 @@ where
     J1: Fetch + 'w,
     D: WorldData,
 {
-    data: &'w D,
     query_iter: WorldFetchIter<'w, (<D::Entity as Entity>::FetchId<'w>, F), D>,
     nest_fetch0: D::Fetch<'w, J0>,
     nest_fetch1: D::Fetch<'w, J1>,
@@ -65,7 +62,6 @@ where
         let nest_fetch1 = self.data.fetch();
 
         Nest2DataFetchIter {
-            data: self.data,
             query_iter,
             nest_fetch0,
             nest_fetch1,
@@ -86,7 +82,6 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         let (id, item) = self.query_iter.next()?;
         let nest = Nest2 {
-            data: self.data,
             ignore_id: id,
             fetch0: self.nest_fetch0.clone(),
             fetch1: self.nest_fetch1.clone(),
@@ -121,7 +116,6 @@ where
         }
 
         let nest1 = Nest1 {
-            data: self.data,
             ignore_ids: [self.ignore_id, id],
             fetch1: self.fetch1.clone(),
         };
@@ -3,7 +3,7 @@ use std::cell::UnsafeCell;
 use downcast_rs::Downcast;
 use fxhash::FxHashMap;
 
-use crate::{Component, Entity, Id};
+use crate::{stats::ColumnStats, Component, Entity, Id};
 
 pub struct SecondaryColumn<E: Entity, C>(FxHashMap<Id<E>, UnsafeCell<C>>);
 
@@ -33,6 +33,8 @@ impl<E: Entity, C> SecondaryColumn<E, C> {
 
 pub trait AnySecondaryColumn<E: Entity>: Downcast + 'static {
     fn remove(&mut self, id: Id<E>);
+
+    fn stats(&self) -> ColumnStats;
 }
 
 downcast_rs::impl_downcast!(AnySecondaryColumn<E> where E: Entity);
@@ -41,4 +43,8 @@ impl<E: Entity, C: Component> AnySecondaryColumn<E> for SecondaryColumn<E, C> {
     fn remove(&mut self, id: Id<E>) {
         self.0.remove(&id);
     }
+
+    fn stats(&self) -> ColumnStats {
+        ColumnStats::new::<C, (Id<E>, C)>(self.0.len(), self.0.capacity())
+    }
 }
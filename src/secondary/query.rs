@@ -19,7 +19,9 @@ pub trait SecondaryFetch<'w, E: Entity>: Copy + 'w {
         Self: 'a;
 }
 
-// This is unafe because `for_each_borrow` must match `Fetch`.
+/// # Safety
+///
+/// This is unsafe because `for_each_borrow` must match `Fetch`.
 pub unsafe trait SecondaryQuery<E: Entity> {
     type Fetch<'w>: SecondaryFetch<'w, E>;
 
@@ -62,7 +64,7 @@ impl<'w, E: Entity, C: Component> SecondaryFetch<'w, E> for ComponentFetch<'w, E
     }
 }
 
-unsafe impl<'q, E: Entity, C: Component> SecondaryQuery<E> for &'q C {
+unsafe impl<E: Entity, C: Component> SecondaryQuery<E> for &C {
     type Fetch<'w> = ComponentFetch<'w, E, C>;
 
     fn for_each_borrow(mut f: impl FnMut(TypeId, bool)) {
@@ -70,7 +72,7 @@ unsafe impl<'q, E: Entity, C: Component> SecondaryQuery<E> for &'q C {
     }
 }
 
-impl<'q, E: Entity, C: Component> SecondaryQueryShared<E> for &'q C {}
+impl<E: Entity, C: Component> SecondaryQueryShared<E> for &C {}
 
 pub struct ComponentMutFetch<'w, E: Entity, C>(&'w SecondaryColumn<E, C>);
 
@@ -103,7 +105,7 @@ impl<'w, E: Entity, C: Component> SecondaryFetch<'w, E> for ComponentMutFetch<'w
     }
 }
 
-unsafe impl<'q, E: Entity, C: Component> SecondaryQuery<E> for &'q mut C {
+unsafe impl<E: Entity, C: Component> SecondaryQuery<E> for &mut C {
     type Fetch<'w> = ComponentMutFetch<'w, E, C>;
 
     fn for_each_borrow(mut f: impl FnMut(TypeId, bool)) {
@@ -111,7 +113,7 @@ unsafe impl<'q, E: Entity, C: Component> SecondaryQuery<E> for &'q mut C {
     }
 }
 
-impl<'q, E: Entity, C: Component> SecondaryQueryShared<E> for &'q mut C {}
+impl<E: Entity, C: Component> SecondaryQueryShared<E> for &mut C {}
 
 macro_rules! tuple_impl {
     ($($name: ident),*) => {
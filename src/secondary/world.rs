@@ -3,7 +3,8 @@ use std::{any::TypeId, marker::PhantomData};
 use fxhash::{FxHashMap, FxHashSet};
 
 use crate::{
-    entity::EntityVariant, query::fetch::Fetch, Component, Entity, EntityRef, Id, Query, World,
+    entity::EntityVariant, query::fetch::Fetch, stats::ColumnStats, Component, Entity, EntityRef,
+    Id, Query, World,
 };
 
 use super::column::{AnySecondaryColumn, SecondaryColumn};
@@ -40,6 +41,16 @@ impl<E: Entity> SecondaryWorld<E> {
             .and_then(|column| column.downcast_ref())
     }
 
+    /// Reports the memory used by the columns. Since columns are hash maps,
+    /// the bytes are estimated from the size of their entries.
+    pub fn memory_stats(&self) -> SecondaryMemoryStats {
+        SecondaryMemoryStats {
+            len: self.ids.len(),
+            ids: ColumnStats::new::<Id<E>, Id<E>>(self.ids.len(), self.ids.capacity()),
+            columns: self.columns.values().map(|column| column.stats()).collect(),
+        }
+    }
+
     pub fn spawn<B: ComponentBundle<E>>(&mut self, id: Id<E>, components: B) -> bool {
         if !self.ids.insert(id) {
             return false;
@@ -100,6 +111,20 @@ impl<E: Entity> SecondaryWorld<E> {
     }
 }
 
+/// Memory usage of a [`SecondaryWorld`].
+#[derive(Clone, Debug)]
+pub struct SecondaryMemoryStats {
+    pub len: usize,
+    pub ids: ColumnStats,
+    pub columns: Vec<ColumnStats>,
+}
+
+impl SecondaryMemoryStats {
+    pub fn bytes(&self) -> usize {
+        self.ids.bytes + self.columns.iter().map(|c| c.bytes).sum::<usize>()
+    }
+}
+
 pub trait ComponentBundle<E: Entity> {
     fn insert_entity(self, world: &mut SecondaryWorld<E>, id: Id<E>);
 }
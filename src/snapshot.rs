@@ -0,0 +1,53 @@
+use derivative::Derivative;
+
+use crate::{column::Column, Entity, World};
+
+/// Storage that can be made equal to another instance of itself by copying
+/// only the parts of columns that differ.
+///
+/// This is implemented by `derive(Entity)` if all components implement
+/// `Clone`.
+pub trait CloneFromChanged {
+    fn clone_from_changed(&mut self, source: &Self);
+}
+
+impl<C: Clone> CloneFromChanged for Column<C> {
+    fn clone_from_changed(&mut self, source: &Self) {
+        Column::clone_from_changed(self, source);
+    }
+}
+
+/// A copy of the state of a [`World`], created with [`World::snapshot`].
+///
+/// Snapshots are meant to be reused: [`World::snapshot_into`] and
+/// [`World::restore`] only copy the chunks of 64 components that have been
+/// mutably accessed in the meantime, so their cost is roughly proportional to
+/// the amount of data that changed. Spawning or despawning entities
+/// additionally copies the `Id` slots of their archetype.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "E::WorldData: Clone"), Default(bound = ""))]
+pub struct Snapshot<E: Entity>(E::WorldData);
+
+impl<E: Entity> World<E>
+where
+    E::WorldData: CloneFromChanged,
+{
+    pub fn snapshot(&self) -> Snapshot<E> {
+        let mut snapshot = Snapshot::default();
+        self.snapshot_into(&mut snapshot);
+
+        snapshot
+    }
+
+    /// Updates `snapshot` to match the current state of the world.
+    pub fn snapshot_into(&self, snapshot: &mut Snapshot<E>) {
+        snapshot.0.clone_from_changed(&self.0);
+    }
+
+    /// Resets the world to the state stored in `snapshot`.
+    ///
+    /// Entities keep the `Id`s that they had when the snapshot was taken.
+    pub fn restore(&mut self, snapshot: &Snapshot<E>) {
+        self.0.clone_from_changed(&snapshot.0);
+    }
+}
@@ -0,0 +1,110 @@
+use std::any::TypeId;
+
+/// Memory usage of a world, as returned by
+/// [`World::memory_stats`](crate::World::memory_stats).
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStats {
+    pub archetypes: Vec<ArchetypeStats>,
+}
+
+impl MemoryStats {
+    /// The number of entities in all archetypes.
+    pub fn len(&self) -> usize {
+        self.archetypes.iter().map(|archetype| archetype.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of bytes allocated for columns in all archetypes.
+    pub fn bytes(&self) -> usize {
+        self.archetypes.iter().map(ArchetypeStats::bytes).sum()
+    }
+
+    /// Sums up the column statistics of all archetypes by component type.
+    pub fn by_component(&self) -> Vec<ColumnStats> {
+        let mut result: Vec<ColumnStats> = Vec::new();
+
+        for (_, column) in self.archetypes.iter().flat_map(|a| &a.columns) {
+            match result.iter_mut().find(|c| c.type_id == column.type_id) {
+                Some(total) => {
+                    total.len += column.len;
+                    total.capacity += column.capacity;
+                    total.bytes += column.bytes;
+                }
+                None => result.push(column.clone()),
+            }
+        }
+
+        result
+    }
+}
+
+/// Memory usage of the archetype of one struct entity type.
+#[derive(Clone, Debug)]
+pub struct ArchetypeStats {
+    /// The names of the enum variants that lead to this archetype, starting
+    /// with the outermost enum. Empty if the world stores the struct directly.
+    pub variant: Vec<&'static str>,
+
+    /// The name of the entity type, without module path and generics.
+    pub entity: &'static str,
+
+    pub len: usize,
+
+    /// The number of slots that the id arena has allocated memory for.
+    pub slots: usize,
+
+    /// The number of entities that can be spawned without reallocating the
+    /// id arena, counting both vacant slots and unused capacity.
+    ///
+    /// This is not the number of vacant slots: `thunderdome::Arena` does not
+    /// expose how many slots have been despawned, so vacant slots cannot be
+    /// told apart from capacity that has never been used. It is an upper
+    /// bound on the vacant slots.
+    pub spare_capacity: usize,
+
+    /// The column that maps storage indices back to ids.
+    pub ids: ColumnStats,
+
+    /// The component columns, together with their field path. Components of
+    /// flattened entities have paths such as `pos.x`.
+    pub columns: Vec<(String, ColumnStats)>,
+}
+
+impl ArchetypeStats {
+    /// The number of bytes allocated for the component and id columns. This
+    /// does not include the id arena, whose entry layout is internal to
+    /// `thunderdome`.
+    pub fn bytes(&self) -> usize {
+        self.ids.bytes + self.columns.iter().map(|(_, c)| c.bytes).sum::<usize>()
+    }
+}
+
+/// Memory usage of a single column, either of an archetype or of a
+/// [`SecondaryWorld`](crate::secondary::world::SecondaryWorld).
+#[derive(Clone, Debug)]
+pub struct ColumnStats {
+    pub type_name: &'static str,
+    pub type_id: TypeId,
+    pub len: usize,
+    pub capacity: usize,
+
+    /// The number of bytes allocated for `capacity` elements.
+    pub bytes: usize,
+}
+
+impl ColumnStats {
+    /// Statistics for a column that stores elements of type `T`. Note that
+    /// `T` may differ from `C`, e.g. for hash map entries.
+    pub fn new<C: 'static, T>(len: usize, capacity: usize) -> Self {
+        Self {
+            type_name: std::any::type_name::<C>(),
+            type_id: TypeId::of::<C>(),
+            len,
+            capacity,
+            bytes: capacity * std::mem::size_of::<T>(),
+        }
+    }
+}
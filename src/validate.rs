@@ -0,0 +1,25 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// A broken internal invariant, as reported by
+/// [`World::validate`](crate::World::validate).
+///
+/// This always indicates a bug in `stecs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError(String);
+
+impl ValidationError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid world: {}", self.0)
+    }
+}
+
+impl Error for ValidationError {}
@@ -1,10 +1,18 @@
-use std::fmt::Debug;
+use std::{any::TypeId, fmt::Debug, hash::Hash, marker::PhantomData};
 
 use derivative::Derivative;
 
 use crate::{
-    entity::EntityVariant,
-    query::{assert_borrow, fetch::Fetch, QueryBorrow, QueryItem, QueryMut, QueryShared},
+    checksum::{self, Checksums},
+    dynamic::{AnyColumn, DynArchetype},
+    entity::{EntityStruct, EntityVariant},
+    layout::EntityLayout,
+    query::{
+        assert_borrow, fetch::Fetch, iter::WorldFetchIter, QueryBorrow, QueryItem, QueryMut,
+        QueryShared,
+    },
+    stats::MemoryStats,
+    validate::ValidationError,
     Entity, EntityRef, EntityRefMut, Id, Query,
 };
 
@@ -12,6 +20,10 @@ pub trait WorldFetch<'w, F: Fetch>: Clone {
     type Data: WorldData;
     type Iter: Iterator<Item = F>;
 
+    /// # Safety
+    ///
+    /// The caller has to ensure that the returned item does not alias any
+    /// other borrows of the same entity's components.
     unsafe fn get<'a>(
         &self,
         id: <<Self::Data as WorldData>::Entity as Entity>::Id,
@@ -20,9 +32,15 @@ pub trait WorldFetch<'w, F: Fetch>: Clone {
     fn iter(&mut self) -> Self::Iter;
 
     fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
-pub trait WorldData: Default + 'static {
+pub trait WorldData:
+    IntoIterator<Item = (Id<Self::Entity>, Self::Entity)> + Default + 'static
+{
     type Entity: EntityVariant<Self::Entity>;
 
     type Fetch<'w, F: Fetch + 'w>: WorldFetch<'w, F, Data = Self>;
@@ -39,6 +57,56 @@ pub trait WorldData: Default + 'static {
 
     fn contains(&self, id: Id<Self::Entity>) -> bool;
 
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn clear(&mut self);
+
+    fn reserve<E>(&mut self, additional: usize)
+    where
+        E: EntityVariant<Self::Entity>;
+
+    fn shrink_to_fit(&mut self);
+
+    fn defragment(&mut self);
+
+    fn describe(&self) -> EntityLayout;
+
+    fn memory_stats(&self) -> MemoryStats;
+
+    /// Checks the internal invariants of the storage of each struct entity.
+    fn validate(&self) -> Result<(), ValidationError>;
+
+    /// Returns the column of component type `type_id` and the index of the
+    /// entity `id` in it.
+    #[doc(hidden)]
+    fn dyn_column(&self, id: Id<Self::Entity>, type_id: TypeId) -> Option<(&dyn AnyColumn, usize)>;
+
+    /// Returns the archetypes that have all of the component types
+    /// `type_ids`, with their columns in the order of `type_ids`.
+    #[doc(hidden)]
+    fn dyn_archetypes<'w>(
+        &'w self,
+        type_ids: &'w [TypeId],
+    ) -> Box<dyn Iterator<Item = DynArchetype<'w, Self::Entity>> + 'w>;
+
+    /// Removes the entities matching `F` for which `predicate` returns `true`,
+    /// passing them to `out`. Each archetype is compacted in a single pass.
+    ///
+    /// # Safety
+    ///
+    /// `predicate` must not keep the items that it is given beyond the call,
+    /// since the storage is reordered afterwards.
+    #[doc(hidden)]
+    unsafe fn drain_filter<'w, F, P, O>(&mut self, predicate: &mut P, out: &mut O)
+    where
+        F: Fetch + 'w,
+        P: FnMut(F::Item<'w>) -> bool,
+        O: FnMut(Id<Self::Entity>, Self::Entity);
+
     #[doc(hidden)]
     fn fetch<'w, F>(&'w self) -> Self::Fetch<'w, F>
     where
@@ -51,7 +119,7 @@ pub trait WorldData: Default + 'static {
     Debug(bound = "E::WorldData: Debug"),
     Default(bound = "")
 )]
-pub struct World<E: Entity>(E::WorldData);
+pub struct World<E: Entity>(pub(crate) E::WorldData);
 
 impl<E: Entity> World<E> {
     pub fn new() -> Self {
@@ -64,21 +132,227 @@ impl<E: Entity> World<E> {
     where
         F: EntityVariant<E>,
     {
-        self.0.spawn(entity)
+        let id = self.0.spawn(entity);
+        self.debug_validate_id(id.to_outer(), true);
+
+        id
+    }
+
+    /// Spawns all entities of `entities`, returning their ids in order.
+    ///
+    /// Capacity is reserved up front according to the lower bound of the
+    /// iterator's size hint.
+    pub fn spawn_batch<F, I>(&mut self, entities: I) -> impl ExactSizeIterator<Item = Id<F>>
+    where
+        F: EntityVariant<E>,
+        I: IntoIterator<Item = F>,
+    {
+        let entities = entities.into_iter();
+
+        self.reserve::<F>(entities.size_hint().0);
+
+        let ids: Vec<_> = entities.map(|entity| self.0.spawn(entity)).collect();
+        self.debug_validate();
+
+        ids.into_iter()
+    }
+
+    /// Reserves capacity for at least `additional` more entities of type `F`.
+    ///
+    /// If `F` is an enum, capacity is reserved for each of its variants.
+    ///
+    /// The slots of an archetype's id arena are only preallocated while the
+    /// arena has none yet. `thunderdome::Arena` cannot reserve afterwards, so
+    /// an arena that already has slots is not grown, and only the columns are.
+    pub fn reserve<F>(&mut self, additional: usize)
+    where
+        F: EntityVariant<E>,
+    {
+        self.0.reserve::<F>(additional)
+    }
+
+    /// Shrinks the columns of all archetypes to fit. Like with `reserve`, the
+    /// id arenas keep their slots.
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Sorts the entities of the struct variant `F` by the key returned by
+    /// `f`. The sort is stable, and `Id`s remain valid.
+    pub fn sort_variant_by_key<F, K>(&mut self, f: impl FnMut(EntityRef<'_, F>) -> K)
+    where
+        F: EntityVariant<E> + EntityStruct,
+        K: Ord,
+    {
+        self.variant_mut::<F>().sort_by_key(f);
+        self.debug_validate();
+    }
+
+    /// Sorts the entities of each variant by their `Id` and shrinks the
+    /// storage to fit. `Id`s remain valid. Variants declared with
+    /// `#[stecs(ordered)]` keep their spawn order.
+    pub fn defragment(&mut self) {
+        self.0.defragment();
+        self.debug_validate();
     }
 
     pub fn despawn<F>(&mut self, id: Id<F>) -> Option<E>
     where
         F: EntityVariant<E>,
     {
-        self.0.despawn(id)
+        let entity = self.0.despawn(id);
+        self.debug_validate_id(id.to_outer(), false);
+
+        entity
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.debug_validate();
+    }
+
+    /// Despawns all entities matching `Q` for which `f` returns `false`.
+    ///
+    /// Entities that do not match `Q` are kept. The storage is compacted in a
+    /// single pass, keeping the kept entities in their previous order.
+    pub fn retain<Q: Query>(&mut self, mut f: impl FnMut(QueryItem<'static, '_, Q>) -> bool) {
+        // Safety: Check that the query does not specify borrows that violate
+        // Rust's borrowing rules.
+        assert_borrow::<Q>();
+
+        // NOTE: The lifetime of the fetch does not affect the lifetime of the
+        // items, so we can use `'static` there. This keeps `f` generic only
+        // over the item lifetime.
+
+        // Safety: `f` is generic over the lifetime of the item, so it cannot
+        // keep it.
+        unsafe {
+            self.0
+                .drain_filter::<Q::Fetch<'static>, _, _>(&mut |item| !f(item), &mut |_, _| ());
+        }
+
+        self.debug_validate();
+    }
+
+    /// Despawns all entities matching `Q` for which `f` returns `true`,
+    /// returning them together with their ids.
+    ///
+    /// Entities that do not match `Q` are kept. The storage is compacted in a
+    /// single pass, keeping the kept entities in their previous order.
+    pub fn drain_filter<Q: Query>(
+        &mut self,
+        mut f: impl FnMut(QueryItem<'static, '_, Q>) -> bool,
+    ) -> impl ExactSizeIterator<Item = (Id<E>, E)> {
+        // Safety: Check that the query does not specify borrows that violate
+        // Rust's borrowing rules.
+        assert_borrow::<Q>();
+
+        let mut removed = Vec::new();
+
+        // Safety: `f` is generic over the lifetime of the item, so it cannot
+        // keep it.
+        unsafe {
+            self.0
+                .drain_filter::<Q::Fetch<'static>, _, _>(&mut f, &mut |id, entity| {
+                    removed.push((id, entity))
+                });
+        }
+
+        self.debug_validate();
+
+        removed.into_iter()
+    }
+
+    /// Returns the storage of entity variant `F`.
+    ///
+    /// For struct entities, this is an [`Archetype`](crate::archetype::Archetype),
+    /// with methods such as `iter`, `drain` and `retain`. For enum entities,
+    /// this is their generated world data, which only implements
+    /// [`WorldData`]. To work with the entities of an enum variant, use
+    /// queries for `Id<F>`, which only match the variant's archetypes, or get
+    /// the storages of its struct variants.
+    pub fn variant<F>(&self) -> &F::WorldData
+    where
+        F: EntityVariant<E>,
+    {
+        F::world_data(&self.0)
+    }
+
+    /// Returns the storage of entity variant `F` mutably. See
+    /// [`World::variant`] for what the storage offers.
+    pub fn variant_mut<F>(&mut self) -> &mut F::WorldData
+    where
+        F: EntityVariant<E>,
+    {
+        // Safety: `self.0` is valid, and we have exclusive access to it.
+        unsafe { &mut *F::world_data_ptr(&mut self.0) }
+    }
+
+    /// Returns the storages of multiple entity variants mutably, e.g.
+    /// `world.split_variants_mut::<(Player, Enemy)>()`.
+    ///
+    /// Panics if the variants are not disjoint, or if one of them is `E`
+    /// itself. Only direct variants of `E` can be split off, so nested
+    /// storages cannot overlap.
+    pub fn split_variants_mut<S>(&mut self) -> S::WorldDataMut<'_>
+    where
+        S: SplitVariants<E>,
+    {
+        S::split_mut(&mut self.0)
+    }
+
+    /// Computes a checksum of all entities and their `Id`s, using the `Hash`
+    /// impls of the entity references. Entity types need to be annotated with
+    /// `#[stecs(derive_ref(Hash))]` for this.
+    ///
+    /// The result does not depend on the order in which the entities are
+    /// stored, so it can be compared between worlds that went through the same
+    /// sequence of operations on different machines. Components containing
+    /// strings, slices or `Vec`s hash their length as a `usize`, which
+    /// differs between 32- and 64-bit peers. Use [`World::checksums`] to find
+    /// out which entities differ.
+    pub fn checksum(&self) -> u64
+    where
+        for<'a> EntityRef<'a, E>: Hash,
+    {
+        checksum::combine(
+            self.entities()
+                .map(|(id, entity)| checksum::entity_checksum(id, entity)),
+        )
+    }
+
+    /// Computes a checksum for each entity. See [`World::checksum`].
+    pub fn checksums(&self) -> Checksums<E>
+    where
+        for<'a> EntityRef<'a, E>: Hash,
+    {
+        let mut checksums = Checksums::default();
+
+        for (id, entity) in self.entities() {
+            checksums.insert(id, checksum::entity_checksum(id, entity));
+        }
+
+        checksums
+    }
+
+    pub(crate) fn entities(&self) -> impl Iterator<Item = (Id<E>, EntityRef<'_, E>)> {
+        // Safety: We only hand out shared references.
+        unsafe { WorldFetchIter::<(E::FetchId<'_>, E::Fetch<'_>), _>::new(&self.0) }
     }
 
-    pub fn query<Q: QueryShared>(&self) -> QueryBorrow<Q, E::WorldData> {
+    pub fn query<Q: QueryShared>(&self) -> QueryBorrow<'_, Q, E::WorldData> {
         QueryBorrow::new(&self.0)
     }
 
-    pub fn query_mut<Q: Query>(&mut self) -> QueryMut<Q, E::WorldData> {
+    pub fn query_mut<Q: Query>(&mut self) -> QueryMut<'_, Q, E::WorldData> {
         QueryMut::new(&mut self.0)
     }
 
@@ -90,21 +364,21 @@ impl<E: Entity> World<E> {
         unsafe { Q::new(&self.0) }
     }
 
-    pub fn get<Q: QueryShared>(&self, id: Id<E>) -> Option<QueryItem<Q>> {
+    pub fn get<Q: QueryShared>(&self, id: Id<E>) -> Option<QueryItem<'_, '_, Q>> {
         let fetch = self.0.fetch::<<Q as Query>::Fetch<'_>>();
 
         // Safety: TODO
         unsafe { fetch.get(id.get()) }
     }
 
-    pub fn get_mut<Q: Query>(&mut self, id: Id<E>) -> Option<QueryItem<Q>> {
+    pub fn get_mut<Q: Query>(&mut self, id: Id<E>) -> Option<QueryItem<'_, '_, Q>> {
         let fetch = self.0.fetch::<<Q as Query>::Fetch<'_>>();
 
         // Safety: TODO
         unsafe { fetch.get(id.get()) }
     }
 
-    pub fn entity<F>(&self, id: Id<F>) -> Option<EntityRef<F>>
+    pub fn entity<F>(&self, id: Id<F>) -> Option<EntityRef<'_, F>>
     where
         F: EntityVariant<E>,
     {
@@ -115,7 +389,7 @@ impl<E: Entity> World<E> {
         unsafe { fetch.get(id.get()) }
     }
 
-    pub fn entity_mut<F>(&mut self, id: Id<F>) -> Option<EntityRefMut<F>>
+    pub fn entity_mut<F>(&mut self, id: Id<F>) -> Option<EntityRefMut<'_, F>>
     where
         F: EntityVariant<E>,
     {
@@ -127,7 +401,85 @@ impl<E: Entity> World<E> {
     }
 
     pub fn spawn_at(&mut self, id: Id<E>, entity: E) -> Option<E> {
-        self.0.spawn_at(id, entity)
+        let old = self.0.spawn_at(id, entity);
+        self.debug_validate_id(id, true);
+
+        old
+    }
+
+    /// Describes the layout of the entity types in the world, including the
+    /// number of entities of each type.
+    pub fn describe(&self) -> EntityLayout {
+        self.0.describe()
+    }
+
+    /// Reports the memory used by the entities in the world, per archetype
+    /// and component.
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.0.memory_stats()
+    }
+
+    /// Checks the internal invariants of the world: in each archetype,
+    /// `indices` and `ids` have to agree, all columns have to have the same
+    /// length, and there must be no dangling slots. For enum entities, the
+    /// variant tag of each `Id` has to lead back to its archetype.
+    ///
+    /// With the `debug-checks` feature, this is run after every structural
+    /// change, panicking if an invariant is broken. Single spawns and
+    /// despawns only check the entities they touch, so that they stay `O(1)`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.0.validate()?;
+
+        let mut len = 0;
+
+        for archetype in self.0.dyn_archetypes(&[]) {
+            len += archetype.len;
+
+            for index in 0..archetype.len {
+                let id = (archetype.to_id)(index);
+
+                if !self.0.contains(id) {
+                    return Err(ValidationError::new(format!(
+                        "{id:?} at index {index} does not lead back to its archetype",
+                    )));
+                }
+            }
+        }
+
+        if len != self.0.len() {
+            return Err(ValidationError::new(format!(
+                "archetypes contain {len} entities, but the world reports {}",
+                self.0.len(),
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn debug_validate(&self) {
+        #[cfg(feature = "debug-checks")]
+        if let Err(err) = self.validate() {
+            panic!("{err}");
+        }
+    }
+
+    /// Checks in `O(1)` whether `id` leads to an entity after spawning or
+    /// despawning it. The archetype has already checked its own invariants
+    /// around the entity.
+    #[cfg_attr(not(feature = "debug-checks"), allow(unused_variables))]
+    fn debug_validate_id(&self, id: Id<E>, contains: bool) {
+        #[cfg(feature = "debug-checks")]
+        match (self.0.contains(id), contains) {
+            (false, true) => panic!("invalid world: {id:?} does not lead back to its archetype"),
+            (true, false) => panic!("invalid world: {id:?} still leads to an entity"),
+            _ => (),
+        }
+    }
+
+    /// Decodes `bits` as created by [`Id::to_bits`], returning the `Id` only
+    /// if it belongs to an entity in this world.
+    pub fn validate_id(&self, bits: u64) -> Option<Id<E>> {
+        Id::from_bits(bits).filter(|&id| self.0.contains(id))
     }
 
     pub fn contains<F>(&self, id: Id<F>) -> bool
@@ -141,6 +493,10 @@ impl<E: Entity> World<E> {
 pub trait MultiQuery {
     type QueryBorrows<'w, D: WorldData>;
 
+    /// # Safety
+    ///
+    /// The caller has to ensure that the queries do not borrow components
+    /// that are exclusively borrowed elsewhere.
     unsafe fn new<D: WorldData>(world: &D) -> Self::QueryBorrows<'_, D>;
 }
 
@@ -170,6 +526,66 @@ smaller_tuples_too!(
     tuple_impl, F0, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15
 );
 
+pub trait SplitVariants<E: Entity> {
+    type WorldDataMut<'w>;
+
+    fn split_mut(data: &mut E::WorldData) -> Self::WorldDataMut<'_>;
+}
+
+macro_rules! split_tuple_impl {
+    ($($name: ident),*) => {
+        #[allow(unused)]
+        impl<E: Entity, $($name: EntityVariant<E>,)*> SplitVariants<E> for ($($name,)*) {
+            type WorldDataMut<'w> = ($(&'w mut $name::WorldData,)*);
+
+            #[allow(clippy::unused_unit)]
+            fn split_mut(data: &mut E::WorldData) -> Self::WorldDataMut<'_> {
+                // Each direct variant of `E` lives in its own field of
+                // `E::WorldData`, while `E` itself covers all of them.
+                let variants: &[TypeId] = &[$(TypeId::of::<$name>(),)*];
+
+                for (i, variant) in variants.iter().enumerate() {
+                    assert!(
+                        *variant != TypeId::of::<E>(),
+                        "split_variants_mut: cannot split off the whole world",
+                    );
+                    assert!(
+                        !variants[..i].contains(variant),
+                        "split_variants_mut: variants must be disjoint",
+                    );
+                }
+
+                let data: *mut E::WorldData = data;
+
+                // Safety: We have checked above that the variants are
+                // disjoint, and `world_data_ptr` does not create intermediate
+                // references.
+                ($(unsafe { &mut *$name::world_data_ptr(data) },)*)
+            }
+        }
+    };
+}
+
+smaller_tuples_too!(
+    split_tuple_impl,
+    F0,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15
+);
+
 // For proc macros.
 #[doc(hidden)]
 pub type EntityWorldData<E> = <E as Entity>::WorldData;
@@ -181,3 +597,80 @@ pub type EntityWorldFetch<'w, E, F> = <EntityWorldData<E> as WorldData>::Fetch<'
 // For proc macros.
 #[doc(hidden)]
 pub type EntityWorldFetchIter<'w, E, F> = <EntityWorldFetch<'w, E, F> as WorldFetch<'w, F>>::Iter;
+
+// For proc macros.
+#[doc(hidden)]
+pub struct EntityWorldIntoIter<E: Entity, EOuter>(
+    <EntityWorldData<E> as IntoIterator>::IntoIter,
+    PhantomData<EOuter>,
+);
+
+impl<E, EOuter> EntityWorldIntoIter<E, EOuter>
+where
+    E: EntityVariant<EOuter>,
+    EOuter: Entity,
+{
+    pub fn new(data: E::WorldData) -> Self {
+        Self(data.into_iter(), PhantomData)
+    }
+}
+
+impl<E, EOuter> Iterator for EntityWorldIntoIter<E, EOuter>
+where
+    E: EntityVariant<EOuter>,
+    EOuter: Entity,
+{
+    type Item = (Id<EOuter>, EOuter);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|(id, entity)| (id.to_outer(), entity.into_outer()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<E: Entity> IntoIterator for World<E> {
+    type Item = (Id<E>, E);
+
+    type IntoIter = <E::WorldData as IntoIterator>::IntoIter;
+
+    /// Moves all entities out of the world.
+    ///
+    /// Entities are yielded one variant after the other, each in storage
+    /// order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<E, F> Extend<F> for World<E>
+where
+    E: Entity,
+    F: EntityVariant<E>,
+{
+    fn extend<I: IntoIterator<Item = F>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve::<F>(iter.size_hint().0);
+
+        for entity in iter {
+            self.spawn(entity);
+        }
+    }
+}
+
+impl<E, F> FromIterator<F> for World<E>
+where
+    E: Entity,
+    F: EntityVariant<E>,
+{
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        let mut world = Self::new();
+        world.extend(iter);
+
+        world
+    }
+}